@@ -0,0 +1,103 @@
+use std::io::{self, BufReader};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use crate::compression::decompress;
+use crate::file::File;
+use crate::writer::{StreamWriter, WriteOptions};
+
+/// Writes the buffered JSON to the asynchronous writer, clears the buffer and yields to the runtime.
+async fn drain<W: AsyncWrite + Unpin>(buffer: &mut Vec<u8>, writer: &mut W) -> io::Result<()> {
+    writer.write_all(buffer).await?;
+    buffer.clear();
+    tokio::task::yield_now().await;
+    Ok(())
+}
+
+impl File {
+    /// Returns the file read from the asynchronous reader, decompressed if it starts with gzip or zstd magic bytes.
+    /// Only the transport is asynchronous: the data is read to the end without blocking,
+    /// then it is decompressed and parsed in one go.
+    pub async fn from_async_reader<R: AsyncRead + Unpin>(mut reader: R) -> io::Result<File> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let json = BufReader::new(decompress(&bytes[..])?);
+        Ok(serde_json::from_reader(json)?)
+    }
+
+    /// Writes the file following the options to the asynchronous writer and flushes it.
+    /// Each mesh and element is written into a small buffer, which is sent before yielding to the runtime.
+    pub async fn to_async_writer<W: AsyncWrite + Unpin>(&self, mut writer: W, options: &WriteOptions) -> io::Result<()> {
+        let mut stream = StreamWriter::new(Vec::new(), &self.schema_version, *options)?;
+        for mesh in &self.meshes {
+            stream.write_mesh(mesh)?;
+            drain(stream.get_mut(), &mut writer).await?;
+        }
+        for element in &self.elements {
+            stream.write_element(element)?;
+            drain(stream.get_mut(), &mut writer).await?;
+        }
+        let buffer = stream.finish(&self.info)?;
+        writer.write_all(&buffer).await?;
+        writer.flush().await
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use crate::builder::{ElementBuilder, FileBuilder};
+    use crate::primitives::cuboid;
+    use super::*;
+
+    fn get_file() -> File {
+        let mut builder = FileBuilder::new();
+        builder.info("Author", "Jane Doe");
+        let mesh_id = builder.add_mesh(cuboid(0, 1.0, 2.0, 3.0));
+        builder.add_element(ElementBuilder::new(mesh_id).guid("a").build());
+        builder.build()
+    }
+
+    #[tokio::test]
+    async fn test_to_async_writer_and_from_async_reader() {
+        let file = get_file();
+        let mut buffer = Vec::new();
+        file.to_async_writer(&mut buffer, &WriteOptions::default()).await.unwrap();
+        assert_eq!(String::from_utf8(buffer.clone()).unwrap(), serde_json::to_string(&file).unwrap());
+        let read = File::from_async_reader(&buffer[..]).await.unwrap();
+        assert_eq!(read.eq(&file), true);
+        let options = WriteOptions { pretty: true, ..Default::default() };
+        let mut buffer = Vec::new();
+        file.to_async_writer(&mut buffer, &options).await.unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), crate::writer::to_string_with_options(&file, &options));
+    }
+
+    #[tokio::test]
+    async fn test_async_duplex() {
+        let file = get_file();
+        let (client, server) = tokio::io::duplex(64);
+        let writing = async {
+            file.to_async_writer(client, &WriteOptions { pretty: true, ..Default::default() }).await.unwrap();
+        };
+        let (_, read) = tokio::join!(writing, File::from_async_reader(server));
+        assert_eq!(read.unwrap().eq(&file), true);
+    }
+
+    #[tokio::test]
+    async fn test_from_async_reader_invalid() {
+        let error = File::from_async_reader(&b"{\"schema_version\":"[..]).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        let error = File::from_async_reader(&b"[]"[..]).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_from_async_reader_compressed() {
+        use std::io::Write;
+        use crate::compression::{CompressedWriter, Compression};
+        let file = get_file();
+        let mut writer = CompressedWriter::new(Vec::new(), Compression::Gzip).unwrap();
+        writer.write_all(serde_json::to_string(&file).unwrap().as_bytes()).unwrap();
+        let compressed = writer.finish().unwrap();
+        assert_eq!(File::from_async_reader(&compressed[..]).await.unwrap().eq(&file), true);
+    }
+}
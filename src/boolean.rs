@@ -0,0 +1,750 @@
+use std::mem::take;
+use crate::element::Element;
+use crate::mesh::Mesh;
+use crate::repair::get_welded_points;
+use crate::spatial::BoundingBox;
+use crate::triangulation::triangulate;
+use crate::vector::Vector;
+
+/// Tolerance of the operations relative to the size of the inputs.
+const RELATIVE_TOLERANCE: f64 = 1e-9;
+
+/// Represents the kind of a boolean operation between two solids.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BooleanOperation {
+    /// Keeps everything inside any of the solids.
+    Union,
+    /// Keeps everything inside the first solid and outside the second one.
+    Difference,
+    /// Keeps everything inside both solids.
+    Intersection,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Plane {
+    normal: Vector,
+    w: f64,
+}
+
+impl Plane {
+    fn from_points(a: &Vector, b: &Vector, c: &Vector) -> Option<Plane> {
+        let normal = (*b - *a).cross(&(*c - *a)).normalize();
+        if normal.length() == 0.0 {
+            return None;
+        }
+        Some(Plane { normal, w: normal.dot(a) })
+    }
+
+    fn flip(&mut self) {
+        self.normal = -self.normal;
+        self.w = -self.w;
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Polygon {
+    vertices: Vec<Vector>,
+    plane: Plane,
+    /// Center and radius of a sphere around the vertices, to classify the polygon without visiting them.
+    center: Vector,
+    radius: f64,
+    /// Position of the input triangle the polygon was split from.
+    source: usize,
+}
+
+impl Polygon {
+    fn new(vertices: Vec<Vector>, plane: Plane, source: usize) -> Polygon {
+        let center = vertices.iter().fold(Vector::new(0.0, 0.0, 0.0), |sum, vertex| sum + *vertex) * (1.0 / vertices.len() as f64);
+        let radius = vertices.iter().map(|vertex| vertex.distance_to(&center)).fold(0.0, f64::max);
+        Polygon { vertices, plane, center, radius, source }
+    }
+
+    /// Returns the area of the polygon.
+    fn get_area(&self) -> f64 {
+        let first = self.vertices[0];
+        let doubled = (1..self.vertices.len() - 1)
+            .map(|i| (self.vertices[i] - first).cross(&(self.vertices[i + 1] - first)).dot(&self.plane.normal))
+            .sum::<f64>();
+        doubled.abs() * 0.5
+    }
+
+    /// Returns FRONT or BACK if the sphere around the polygon lies on that side of the plane, SPANNING otherwise.
+    fn get_bounds_side(&self, plane: &Plane, tolerance: f64) -> u8 {
+        let distance = plane.normal.dot(&self.center) - plane.w;
+        if distance > self.radius + tolerance {
+            FRONT
+        } else if distance < -self.radius - tolerance {
+            BACK
+        } else {
+            SPANNING
+        }
+    }
+
+    /// Returns the side of the plane the polygon lies on: COPLANAR, FRONT, BACK or SPANNING.
+    fn get_side(&self, plane: &Plane, tolerance: f64) -> u8 {
+        match self.get_bounds_side(plane, tolerance) {
+            SPANNING => self.vertices.iter().fold(COPLANAR, |all, vertex| all | get_vertex_side(plane, vertex, tolerance)),
+            side => side,
+        }
+    }
+
+    fn flip(&mut self) {
+        self.vertices.reverse();
+        self.plane.flip();
+    }
+}
+
+/// Polygons sorted by the side of a plane they lie on.
+#[derive(Default)]
+struct Split {
+    coplanar_front: Vec<Polygon>,
+    coplanar_back: Vec<Polygon>,
+    front: Vec<Polygon>,
+    back: Vec<Polygon>,
+}
+
+const COPLANAR: u8 = 0;
+const FRONT: u8 = 1;
+const BACK: u8 = 2;
+const SPANNING: u8 = 3;
+
+/// Returns the side of the plane the vertex lies on: COPLANAR, FRONT or BACK.
+fn get_vertex_side(plane: &Plane, vertex: &Vector, tolerance: f64) -> u8 {
+    let distance = plane.normal.dot(vertex) - plane.w;
+    if distance < -tolerance { BACK } else if distance > tolerance { FRONT } else { COPLANAR }
+}
+
+impl Split {
+    /// Adds the polygon to the right list, splitting it in two if the plane crosses it.
+    fn add(&mut self, plane: &Plane, polygon: Polygon, tolerance: f64) {
+        // Most polygons lie on one side, so the types of vertices are kept only for the spanning ones.
+        match polygon.get_side(plane, tolerance) {
+            COPLANAR => {
+                if plane.normal.dot(&polygon.plane.normal) > 0.0 {
+                    self.coplanar_front.push(polygon);
+                } else {
+                    self.coplanar_back.push(polygon);
+                }
+            }
+            FRONT => self.front.push(polygon),
+            BACK => self.back.push(polygon),
+            _ => {
+                let types: Vec<u8> = polygon.vertices.iter().map(|vertex| get_vertex_side(plane, vertex, tolerance)).collect();
+                let count = polygon.vertices.len();
+                let mut front = Vec::with_capacity(count + 1);
+                let mut back = Vec::with_capacity(count + 1);
+                for i in 0..count {
+                    let j = (i + 1) % count;
+                    let (a, b) = (polygon.vertices[i], polygon.vertices[j]);
+                    if types[i] != BACK {
+                        front.push(a);
+                    }
+                    if types[i] != FRONT {
+                        back.push(a);
+                    }
+                    if types[i] | types[j] == SPANNING {
+                        let t = (plane.w - plane.normal.dot(&a)) / plane.normal.dot(&(b - a));
+                        let crossing = a + (b - a) * t;
+                        front.push(crossing);
+                        back.push(crossing);
+                    }
+                }
+                if front.len() >= 3 {
+                    self.front.push(Polygon::new(front, polygon.plane, polygon.source));
+                }
+                if back.len() >= 3 {
+                    self.back.push(Polygon::new(back, polygon.plane, polygon.source));
+                }
+            }
+        }
+    }
+}
+
+/// Node of a binary space partitioning tree, holding polygons lying in its plane.
+#[derive(Default)]
+struct Node {
+    plane: Option<Plane>,
+    /// Position of the front child in the nodes of the tree.
+    front: Option<usize>,
+    /// Position of the back child in the nodes of the tree.
+    back: Option<usize>,
+    polygons: Vec<Polygon>,
+}
+
+/// Largest number of polygons reaching an empty node that simply take the plane of the first of them.
+const LEAF_POLYGONS: usize = 16;
+
+/// Number of polygon planes tried when choosing the plane of a node for more polygons.
+const PLANE_CANDIDATES: usize = 8;
+
+/// Returns the number of polygons lying in front of the plane, behind it, and crossing it.
+fn count_sides(polygons: &[Polygon], plane: &Plane, tolerance: f64) -> (usize, usize, usize) {
+    let mut counts = (0, 0, 0);
+    for polygon in polygons {
+        match polygon.get_side(plane, tolerance) {
+            FRONT => counts.0 += 1,
+            BACK => counts.1 += 1,
+            SPANNING => counts.2 += 1,
+            _ => {}
+        }
+    }
+    counts
+}
+
+/// Returns the plane splitting the polygons reaching an empty node.
+///
+/// The plane of a polygon is preferred, choosing the candidate with similar numbers of polygons on both sides
+/// and few crossing it. Convex parts have all polygons behind the plane of every one of them, so they are
+/// halved by an axis aligned plane through the median of their input vertices instead, which keeps the tree balanced.
+/// Both sides of such a plane get polygons, so leaves always come after a plane of a polygon and stay correctly
+/// inside or outside.
+fn choose_plane(polygons: &[Polygon], sources: &[Polygon], tolerance: f64) -> Plane {
+    if polygons.len() <= LEAF_POLYGONS {
+        return polygons[0].plane;
+    }
+    let mut best: Option<(usize, Plane)> = None;
+    for candidate in 0..PLANE_CANDIDATES {
+        let plane = polygons[candidate * polygons.len() / PLANE_CANDIDATES].plane;
+        let (front, back, spanning) = count_sides(polygons, &plane, tolerance);
+        if front == 0 || back == 0 {
+            continue;
+        }
+        let score = front.abs_diff(back) + 8 * spanning;
+        if best.is_none_or(|(best_score, _)| score < best_score) {
+            best = Some((score, plane));
+        }
+    }
+    if let Some((_, plane)) = best {
+        return plane;
+    }
+
+    // The plane goes through a vertex of the input triangles, as planes passing close to vertices would cut off slivers.
+    let mut bounds = BoundingBox::empty();
+    for polygon in polygons {
+        bounds.extend(&polygon.center);
+    }
+    let size = bounds.size();
+    let normal = if size.x >= size.y && size.x >= size.z {
+        Vector::new(1.0, 0.0, 0.0)
+    } else if size.y >= size.z {
+        Vector::new(0.0, 1.0, 0.0)
+    } else {
+        Vector::new(0.0, 0.0, 1.0)
+    };
+    let (low, high) = (bounds.min.dot(&normal), bounds.max.dot(&normal));
+    let mut positions: Vec<f64> = polygons.iter()
+        .flat_map(|polygon| sources[polygon.source].vertices.iter().map(|vertex| vertex.dot(&normal)))
+        .filter(|position| *position >= low && *position <= high)
+        .collect();
+    if positions.is_empty() {
+        return polygons[0].plane;
+    }
+    let middle = positions.len() / 2;
+    let (_, median, _) = positions.select_nth_unstable_by(middle, f64::total_cmp);
+    let plane = Plane { normal, w: *median };
+    let (front, back, spanning) = count_sides(polygons, &plane, tolerance);
+    if front + spanning == 0 || back + spanning == 0 {
+        return polygons[0].plane;
+    }
+    plane
+}
+
+/// Binary space partitioning tree of a solid, with the root as the first node.
+///
+/// Nodes refer to their children by position and every traversal uses an explicit stack instead of recursion,
+/// so trees of large meshes never overflow the stack.
+#[derive(Default)]
+struct Tree {
+    nodes: Vec<Node>,
+    /// Bounds of the polygons of every node and its descendants, as they were when the tree was built.
+    bounds: Vec<BoundingBox>,
+}
+
+impl Tree {
+    /// Swaps the inside and the outside of the solid.
+    fn invert(&mut self) {
+        for node in self.nodes.iter_mut() {
+            for polygon in node.polygons.iter_mut() {
+                polygon.flip();
+            }
+            if let Some(plane) = node.plane.as_mut() {
+                plane.flip();
+            }
+            std::mem::swap(&mut node.front, &mut node.back);
+        }
+    }
+
+    /// Returns the position of the node where the walk from the given node stops, with the side of the polygon:
+    /// FRONT or BACK if the node has no child on that side, SPANNING if its plane crosses the polygon
+    /// and COPLANAR if the node is empty.
+    fn walk(&self, mut node_index: usize, polygon: &Polygon, tolerance: f64) -> (usize, u8) {
+        loop {
+            let node = &self.nodes[node_index];
+            let plane = match &node.plane {
+                Some(plane) => plane,
+                None => return (node_index, COPLANAR),
+            };
+            let side = polygon.get_bounds_side(plane, tolerance);
+            let child = match side {
+                FRONT => node.front,
+                BACK => node.back,
+                _ => return (node_index, SPANNING),
+            };
+            match child {
+                Some(child) => node_index = child,
+                None => return (node_index, side),
+            }
+        }
+    }
+
+    /// Returns the parts of the polygons lying outside the solid of the tree.
+    fn clip_polygons(&self, polygons: Vec<Polygon>, tolerance: f64) -> Vec<Polygon> {
+        if self.nodes.is_empty() {
+            return polygons;
+        }
+        let mut result = Vec::new();
+        let mut stack: Vec<(usize, Polygon)> = polygons.into_iter().rev().map(|polygon| (0, polygon)).collect();
+        while let Some((node_index, polygon)) = stack.pop() {
+            // Polygons in front of a leaf are outside the solid, polygons behind it are inside and get dropped.
+            let (node_index, side) = self.walk(node_index, &polygon, tolerance);
+            match side {
+                FRONT | COPLANAR => result.push(polygon),
+                BACK => {}
+                _ if self.bounds[node_index].distance_to_point(&polygon.center) > polygon.radius + tolerance => {
+                    // Polygons away from the surface below the node lie entirely inside or outside, so they are not split.
+                    if !self.is_inside(node_index, &polygon.center) {
+                        result.push(polygon);
+                    }
+                }
+                _ => {
+                    let node = &self.nodes[node_index];
+                    let mut split = Split::default();
+                    if let Some(plane) = &node.plane {
+                        split.add(plane, polygon, tolerance);
+                    }
+                    for piece in split.front.into_iter().chain(split.coplanar_front) {
+                        match node.front {
+                            Some(child) => stack.push((child, piece)),
+                            None => result.push(piece),
+                        }
+                    }
+                    if let Some(child) = node.back {
+                        stack.extend(split.back.into_iter().chain(split.coplanar_back).map(|piece| (child, piece)));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns true if the point away from the surface lies inside the solid, walking down from the given node.
+    fn is_inside(&self, mut node_index: usize, point: &Vector) -> bool {
+        loop {
+            let node = &self.nodes[node_index];
+            let plane = match &node.plane {
+                Some(plane) => plane,
+                None => return false,
+            };
+            let is_front = plane.normal.dot(point) > plane.w;
+            match if is_front { node.front } else { node.back } {
+                Some(child) => node_index = child,
+                None => return !is_front,
+            }
+        }
+    }
+
+    /// Removes all parts of polygons of this tree lying inside the solid of the other tree.
+    fn clip_to(&mut self, other: &Tree, tolerance: f64) {
+        for node in self.nodes.iter_mut() {
+            node.polygons = other.clip_polygons(take(&mut node.polygons), tolerance);
+        }
+    }
+
+    fn get_all_polygons(&self) -> Vec<Polygon> {
+        let mut polygons = Vec::new();
+        if self.nodes.is_empty() {
+            return polygons;
+        }
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            polygons.extend(node.polygons.iter().cloned());
+            stack.extend(node.back);
+            stack.extend(node.front);
+        }
+        polygons
+    }
+
+    /// Returns the position of the front or back child of the node, adding an empty one if it is missing.
+    fn get_or_add_child(&mut self, node_index: usize, is_front: bool) -> usize {
+        let existing = if is_front { self.nodes[node_index].front } else { self.nodes[node_index].back };
+        if let Some(child) = existing {
+            return child;
+        }
+        let child = self.nodes.len();
+        self.nodes.push(Node::default());
+        if is_front {
+            self.nodes[node_index].front = Some(child);
+        } else {
+            self.nodes[node_index].back = Some(child);
+        }
+        child
+    }
+
+    /// Adds the polygons to the tree, splitting them by the planes of existing nodes and choosing planes
+    /// of empty nodes they reach.
+    fn build(&mut self, polygons: Vec<Polygon>, sources: &[Polygon], tolerance: f64) {
+        if polygons.is_empty() {
+            return;
+        }
+        if self.nodes.is_empty() {
+            self.nodes.push(Node::default());
+        }
+        let mut stack = vec![(0, polygons)];
+        while let Some((node_index, polygons)) = stack.pop() {
+            let plane = match self.nodes[node_index].plane {
+                Some(plane) => plane,
+                None => {
+                    let plane = choose_plane(&polygons, sources, tolerance);
+                    self.nodes[node_index].plane = Some(plane);
+                    plane
+                }
+            };
+            let mut split = Split::default();
+            for polygon in polygons {
+                split.add(&plane, polygon, tolerance);
+            }
+            let node = &mut self.nodes[node_index];
+            node.polygons.append(&mut split.coplanar_front);
+            node.polygons.append(&mut split.coplanar_back);
+            for (pieces, is_front) in [(split.front, true), (split.back, false)] {
+                if !pieces.is_empty() {
+                    stack.push((self.get_or_add_child(node_index, is_front), pieces));
+                }
+            }
+        }
+
+        // Children come after their parents, so the bounds of descendants are known before the bounds of the node.
+        self.bounds = self.nodes.iter().map(|node| {
+            let mut bounds = BoundingBox::empty();
+            for polygon in &node.polygons {
+                polygon.vertices.iter().for_each(|vertex| bounds.extend(vertex));
+            }
+            bounds
+        }).collect();
+        for node_index in (0..self.nodes.len()).rev() {
+            let node = &self.nodes[node_index];
+            for child in node.front.into_iter().chain(node.back) {
+                if !self.bounds[child].is_empty() {
+                    self.bounds[node_index] = self.bounds[node_index].union(&self.bounds[child]);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the polygons of valid triangles of the mesh, numbering their sources from the given one.
+fn get_polygons(mesh: &Mesh, first_source: usize) -> Vec<Polygon> {
+    (0..mesh.triangles_count())
+        .filter_map(|index| mesh.get_triangle(index))
+        .filter_map(|triangle| {
+            let vertices: Vec<Vector> = triangle.iter().map(|vertex| mesh.get_vertex(*vertex)).collect();
+            Plane::from_points(&vertices[0], &vertices[1], &vertices[2]).map(|plane| (vertices, plane))
+        })
+        .enumerate()
+        .map(|(index, (vertices, plane))| Polygon::new(vertices, plane, first_source + index))
+        .collect()
+}
+
+/// Returns the polygons with the pieces of every source replaced by the source itself, if all of its area is kept.
+/// Polygons are split by planes of the other solid and by balancing planes even where nothing is cut away.
+fn merge_whole_sources(polygons: Vec<Polygon>, sources: &[Polygon]) -> Vec<Polygon> {
+    let mut areas = vec![0.0; sources.len()];
+    let mut counts = vec![0; sources.len()];
+    let mut flipped_counts = vec![0; sources.len()];
+    for polygon in &polygons {
+        areas[polygon.source] += polygon.get_area();
+        counts[polygon.source] += 1;
+        if polygon.plane.normal.dot(&sources[polygon.source].plane.normal) < 0.0 {
+            flipped_counts[polygon.source] += 1;
+        }
+    }
+    let is_whole = |source: usize| {
+        let area = sources[source].get_area();
+        counts[source] > 1 && (flipped_counts[source] == 0 || flipped_counts[source] == counts[source])
+            && (areas[source] - area).abs() <= RELATIVE_TOLERANCE * area
+    };
+
+    let mut result = Vec::with_capacity(polygons.len());
+    let mut added = vec![false; sources.len()];
+    for polygon in polygons {
+        let source = polygon.source;
+        if !is_whole(source) {
+            result.push(polygon);
+        } else if !added[source] {
+            added[source] = true;
+            let mut whole = sources[source].clone();
+            if flipped_counts[source] > 0 {
+                whole.flip();
+            }
+            result.push(whole);
+        }
+    }
+    result
+}
+
+/// Returns the mesh made of the polygons, with vertices shared between them.
+/// Points lying on edges of other polygons are inserted into those edges, so the result has no T-junctions.
+fn get_mesh(mesh_id: i32, polygons: &[Polygon], tolerance: f64) -> Mesh {
+    let all_points: Vec<Vector> = polygons.iter().flat_map(|polygon| polygon.vertices.iter().copied()).collect();
+    let (points, point_map) = get_welded_points(&all_points, tolerance);
+    let mut sorted_by_x: Vec<usize> = (0..points.len()).collect();
+    sorted_by_x.sort_by(|a, b| points[*a].x.total_cmp(&points[*b].x));
+
+    let mut indices = Vec::new();
+    let mut start = 0;
+    for polygon in polygons {
+        let corners = &point_map[start..start + polygon.vertices.len()];
+        start += polygon.vertices.len();
+
+        let mut loop_indices: Vec<usize> = Vec::new();
+        for i in 0..corners.len() {
+            let (a, b) = (corners[i], corners[(i + 1) % corners.len()]);
+            loop_indices.push(a);
+            if a == b {
+                continue;
+            }
+            let (pa, pb) = (points[a], points[b]);
+            let edge = pb - pa;
+            let length = edge.length();
+            let low = sorted_by_x.partition_point(|index| points[*index].x < pa.x.min(pb.x) - tolerance);
+            let mut on_edge: Vec<(f64, usize)> = Vec::new();
+            for &candidate in &sorted_by_x[low..] {
+                let point = points[candidate];
+                if point.x > pa.x.max(pb.x) + tolerance {
+                    break;
+                }
+                if candidate == a || candidate == b {
+                    continue;
+                }
+                let t = (point - pa).dot(&edge) / (length * length);
+                if t <= 0.0 || t >= 1.0 {
+                    continue;
+                }
+                if (pa + edge * t).distance_to(&point) <= tolerance {
+                    on_edge.push((t, candidate));
+                }
+            }
+            on_edge.sort_by(|x, y| x.0.total_cmp(&y.0));
+            loop_indices.extend(on_edge.iter().map(|(_, index)| *index));
+        }
+        loop_indices.dedup();
+        while loop_indices.len() > 1 && loop_indices[0] == loop_indices[loop_indices.len() - 1] {
+            loop_indices.pop();
+        }
+        if loop_indices.len() < 3 {
+            continue;
+        }
+        let loop_points: Vec<Vector> = loop_indices.iter().map(|index| points[*index]).collect();
+        indices.extend(triangulate(&loop_points, &[]).iter().map(|index| loop_indices[*index as usize] as i32));
+    }
+
+    let coordinates = points.iter().flat_map(|point| [point.x, point.y, point.z]).collect();
+    let mut mesh = Mesh::new(mesh_id, coordinates, indices);
+    mesh.remove_degenerate_triangles();
+    mesh.remove_unreferenced_vertices();
+    mesh
+}
+
+/// Returns the result of the boolean operation between two closed meshes, with the mesh_id of the first one.
+///
+/// The operation uses binary space partitioning of the triangles, so both inputs need consistent
+/// outward winding, like the primitives and extrusions of this crate have.
+pub fn boolean(a: &Mesh, b: &Mesh, operation: BooleanOperation) -> Mesh {
+    let mut bounds = BoundingBox::empty();
+    for mesh in [a, b] {
+        for index in 0..mesh.vertices_count() {
+            bounds.extend(&mesh.get_vertex(index));
+        }
+    }
+    let scale = if bounds.is_empty() { 1.0 } else { bounds.size().length().max(1.0) };
+    let tolerance = RELATIVE_TOLERANCE * scale;
+
+    let polygons_a = get_polygons(a, 0);
+    let polygons_b = get_polygons(b, polygons_a.len());
+    let sources: Vec<Polygon> = polygons_a.iter().chain(&polygons_b).cloned().collect();
+    let mut first = Tree::default();
+    first.build(polygons_a, &sources, tolerance);
+    let mut second = Tree::default();
+    second.build(polygons_b, &sources, tolerance);
+
+    match operation {
+        BooleanOperation::Union => {
+            first.clip_to(&second, tolerance);
+            second.clip_to(&first, tolerance);
+            second.invert();
+            second.clip_to(&first, tolerance);
+            second.invert();
+        }
+        BooleanOperation::Difference => {
+            first.invert();
+            first.clip_to(&second, tolerance);
+            second.clip_to(&first, tolerance);
+            second.invert();
+            second.clip_to(&first, tolerance);
+            first.invert();
+        }
+        BooleanOperation::Intersection => {
+            first.invert();
+            second.clip_to(&first, tolerance);
+            second.invert();
+            first.clip_to(&second, tolerance);
+            second.clip_to(&first, tolerance);
+            first.invert();
+            second.invert();
+        }
+    }
+    // The kept polygons of both trees form the result, they are not inserted into one tree to avoid splitting them further.
+    let mut polygons = first.get_all_polygons();
+    polygons.extend(second.get_all_polygons());
+    let polygons = merge_whole_sources(polygons, &sources);
+    get_mesh(a.mesh_id, &polygons, tolerance)
+}
+
+impl Mesh {
+    /// Returns the union of this closed Mesh and the other one.
+    pub fn union(&self, other: &Mesh) -> Mesh { boolean(self, other, BooleanOperation::Union) }
+
+    /// Returns this closed Mesh with the other one subtracted.
+    pub fn difference(&self, other: &Mesh) -> Mesh { boolean(self, other, BooleanOperation::Difference) }
+
+    /// Returns the common part of this closed Mesh and the other one.
+    pub fn intersection(&self, other: &Mesh) -> Mesh { boolean(self, other, BooleanOperation::Intersection) }
+}
+
+impl Element {
+    /// Returns the result of the boolean operation between the meshes of this and the other element,
+    /// placed in the world space, as a mesh in the local space of this element.
+    pub fn boolean(&self, mesh: &Mesh, other: &Element, other_mesh: &Mesh, operation: BooleanOperation) -> Mesh {
+        let coordinates = other
+            .get_world_vertices(other_mesh)
+            .iter()
+            .flat_map(|vertex| {
+                let local = self.inverse_transform_point(vertex);
+                [local.x, local.y, local.z]
+            })
+            .collect();
+        let other_local = Mesh::new(other_mesh.mesh_id, coordinates, other_mesh.indices.clone());
+        boolean(mesh, &other_local, operation)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::color::Color;
+    use crate::primitives::{cuboid, cylinder, icosphere};
+    use crate::rotation::Rotation;
+    use super::*;
+
+    fn assert_solid(mesh: &Mesh, expected_volume: f64) {
+        let report = mesh.analyze_topology();
+        assert_eq!(report.is_valid_solid(), true);
+        assert_eq!((report.signed_volume - expected_volume).abs() < 1e-9, true);
+    }
+
+    fn get_moved(mesh: &Mesh, offset: Vector) -> Mesh {
+        let mut moved = mesh.clone();
+        for vertex in moved.coordinates.chunks_mut(3) {
+            vertex[0] += offset.x;
+            vertex[1] += offset.y;
+            vertex[2] += offset.z;
+        }
+        moved
+    }
+
+    #[test]
+    fn test_union() {
+        let a = cuboid(1, 2.0, 2.0, 2.0);
+        let b = get_moved(&cuboid(2, 2.0, 2.0, 2.0), Vector::new(1.0, 1.0, 1.0));
+        let actual = a.union(&b);
+        assert_eq!(actual.mesh_id, 1);
+        assert_solid(&actual, 8.0 + 8.0 - 1.0);
+    }
+
+    #[test]
+    fn test_difference_opening() {
+        // A wall with an opening going through it
+        let wall = cuboid(0, 4.0, 0.5, 3.0);
+        let opening = get_moved(&cuboid(1, 1.0, 2.0, 2.0), Vector::new(0.0, 0.0, 0.5));
+        let actual = wall.difference(&opening);
+        assert_solid(&actual, 4.0 * 0.5 * 3.0 - 1.0 * 0.5 * 2.0);
+        assert_eq!(actual.analyze_topology().components.len(), 1);
+    }
+
+    #[test]
+    fn test_intersection() {
+        let a = cuboid(0, 2.0, 2.0, 2.0);
+        let b = get_moved(&cuboid(1, 2.0, 2.0, 2.0), Vector::new(1.0, 0.5, 1.5));
+        assert_solid(&a.intersection(&b), 1.0 * 1.5 * 0.5);
+    }
+
+    #[test]
+    fn test_touching_and_disjoint() {
+        let a = cuboid(0, 2.0, 2.0, 2.0);
+        let touching = get_moved(&a, Vector::new(2.0, 0.0, 0.0));
+        assert_solid(&a.union(&touching), 16.0);
+        let far = get_moved(&a, Vector::new(10.0, 0.0, 0.0));
+        assert_solid(&a.difference(&far), 8.0);
+        assert_eq!(a.intersection(&far).triangles_count(), 0);
+    }
+
+    #[test]
+    fn test_difference_cylinder() {
+        let slab = cuboid(0, 4.0, 4.0, 1.0);
+        let void = get_moved(&cylinder(1, 1.0, 3.0, 16), Vector::new(0.0, 0.0, -1.0));
+        let actual = slab.difference(&void);
+        let hole = cylinder(1, 1.0, 1.0, 16).analyze_topology().signed_volume;
+        assert_solid(&actual, 16.0 - hole);
+    }
+
+    #[test]
+    fn test_difference_on_small_stack() {
+        // Trees are traversed without recursion, so meshes with thousands of triangles fit into a small stack
+        let sphere = icosphere(0, 1.0, 2);
+        let drill = get_moved(&cylinder(1, 0.3, 3.0, 32), Vector::new(0.0, 0.0, -1.5));
+        let (difference, intersection) = std::thread::Builder::new()
+            .stack_size(32 * 1024)
+            .spawn(move || (sphere.difference(&drill), sphere.intersection(&drill)))
+            .unwrap()
+            .join()
+            .unwrap();
+        let report = difference.analyze_topology();
+        assert_eq!(report.is_valid_solid(), true);
+        let volume = icosphere(0, 1.0, 2).analyze_topology().signed_volume;
+        let removed = intersection.analyze_topology().signed_volume;
+        assert_eq!(removed > 0.25, true);
+        assert_eq!((report.signed_volume + removed - volume).abs() < 1e-9, true);
+    }
+
+    #[test]
+    fn test_element_boolean() {
+        let half = std::f64::consts::FRAC_PI_4;
+        let wall_mesh = cuboid(0, 4.0, 0.5, 3.0);
+        let opening_mesh = cuboid(1, 1.0, 2.0, 2.0);
+        // The wall is turned by 90 degrees around Z, so the opening has to be turned as well to cross it.
+        let wall = Element::new(0, Vector::new(10.0, 0.0, 0.0), Rotation::new(0.0, 0.0, half.sin(), half.cos()),
+                                String::from("wall"), String::from("Wall"), Color::new(0, 0, 0, 255), None, HashMap::new());
+        let opening = Element::new(1, Vector::new(10.0, 1.0, 0.5), Rotation::new(0.0, 0.0, half.sin(), half.cos()),
+                                   String::from("opening"), String::from("Opening"), Color::new(0, 0, 0, 255), None, HashMap::new());
+        let actual = wall.boolean(&wall_mesh, &opening, &opening_mesh, BooleanOperation::Difference);
+        assert_eq!(actual.mesh_id, 0);
+        assert_solid(&actual, 6.0 - 1.0);
+        // The opening is found one unit along the local X axis of the wall
+        let expected_opening = get_moved(&cuboid(2, 1.0, 2.0, 2.0), Vector::new(1.0, 0.0, 0.5));
+        assert_eq!(actual.intersection(&expected_opening).triangles_count(), 0);
+        let mirrored_opening = get_moved(&expected_opening, Vector::new(-2.0, 0.0, 0.0));
+        assert_solid(&actual.intersection(&mirrored_opening), 1.0);
+    }
+}
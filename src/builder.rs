@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use uuid::Uuid;
+use crate::color::Color;
+use crate::element::Element;
+use crate::file::File;
+use crate::mesh::Mesh;
+use crate::rotation::Rotation;
+use crate::vector::Vector;
+
+/// Builds an Element step by step, starting from defaults: placed at the origin without rotation,
+/// with a random guid, the type "Other", opaque white color, no face colors and no info.
+#[derive(Clone, Debug)]
+pub struct ElementBuilder {
+    mesh_id: i32,
+    vector: Vector,
+    rotation: Rotation,
+    guid: Option<String>,
+    element_type: String,
+    color: Color,
+    face_colors: Option<Vec<i32>>,
+    info: HashMap<String, String>,
+}
+
+impl ElementBuilder {
+    /// Returns a new ElementBuilder for an element of the mesh with the given mesh_id.
+    pub fn new(mesh_id: i32) -> ElementBuilder {
+        ElementBuilder {
+            mesh_id,
+            vector: Vector::new(0.0, 0.0, 0.0),
+            rotation: Rotation::new(0.0, 0.0, 0.0, 1.0),
+            guid: None,
+            element_type: String::from("Other"),
+            color: Color::new(255, 255, 255, 255),
+            face_colors: None,
+            info: HashMap::new(),
+        }
+    }
+
+    /// Sets the position of the element.
+    pub fn vector(mut self, vector: Vector) -> ElementBuilder {
+        self.vector = vector;
+        self
+    }
+
+    /// Sets the rotation of the element.
+    pub fn rotation(mut self, rotation: Rotation) -> ElementBuilder {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Sets the guid of the element instead of the random one.
+    pub fn guid(mut self, guid: &str) -> ElementBuilder {
+        self.guid = Some(String::from(guid));
+        self
+    }
+
+    /// Sets the type of the element.
+    pub fn element_type(mut self, element_type: &str) -> ElementBuilder {
+        self.element_type = String::from(element_type);
+        self
+    }
+
+    /// Sets the color of the element.
+    pub fn color(mut self, color: Color) -> ElementBuilder {
+        self.color = color;
+        self
+    }
+
+    /// Sets face colors of the element, as r, g, b, a values for every triangle.
+    pub fn face_colors(mut self, face_colors: Vec<i32>) -> ElementBuilder {
+        self.face_colors = Some(face_colors);
+        self
+    }
+
+    /// Adds the key with the value to the information about the element.
+    pub fn info(mut self, key: &str, value: &str) -> ElementBuilder {
+        self.info.insert(String::from(key), String::from(value));
+        self
+    }
+
+    /// Returns the Element, with a new random guid if none was set.
+    pub fn build(self) -> Element {
+        let guid = self.guid.unwrap_or_else(|| Uuid::new_v4().to_string());
+        Element::new(self.mesh_id, self.vector, self.rotation, guid, self.element_type, self.color, self.face_colors, self.info)
+    }
+}
+
+/// Builds a File step by step, with the schema version "1.0.0" and no info by default.
+#[derive(Clone, Debug)]
+pub struct FileBuilder {
+    schema_version: String,
+    meshes: Vec<Mesh>,
+    elements: Vec<Element>,
+    info: HashMap<String, String>,
+}
+
+impl Default for FileBuilder {
+    fn default() -> Self { FileBuilder::new() }
+}
+
+impl FileBuilder {
+    /// Returns a new FileBuilder
+    pub fn new() -> FileBuilder {
+        FileBuilder { schema_version: String::from("1.0.0"), meshes: Vec::new(), elements: Vec::new(), info: HashMap::new() }
+    }
+
+    /// Sets the schema version of the file.
+    pub fn schema_version(&mut self, schema_version: &str) -> &mut FileBuilder {
+        self.schema_version = String::from(schema_version);
+        self
+    }
+
+    /// Adds the key with the value to the information about the file.
+    pub fn info(&mut self, key: &str, value: &str) -> &mut FileBuilder {
+        self.info.insert(String::from(key), String::from(value));
+        self
+    }
+
+    /// Adds the mesh under the next free mesh_id, replacing the one it had, and returns that mesh_id.
+    pub fn add_mesh(&mut self, mut mesh: Mesh) -> i32 {
+        mesh.mesh_id = self.meshes.iter().map(|mesh| mesh.mesh_id + 1).max().unwrap_or(0);
+        self.meshes.push(mesh);
+        self.meshes[self.meshes.len() - 1].mesh_id
+    }
+
+    /// Adds the element.
+    pub fn add_element(&mut self, element: Element) -> &mut FileBuilder {
+        self.elements.push(element);
+        self
+    }
+
+    /// Returns the File, leaving the builder as a new one, so it can end a chain of calls like the other methods.
+    pub fn build(&mut self) -> File {
+        let builder = std::mem::take(self);
+        File::new(builder.schema_version, builder.meshes, builder.elements, builder.info)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use crate::primitives::{cuboid, pyramid};
+    use super::*;
+
+    #[test]
+    fn test_element_builder_defaults() {
+        let element = ElementBuilder::new(3).build();
+        assert_eq!(element.mesh_id, 3);
+        assert_eq!(element.vector, Vector::new(0.0, 0.0, 0.0));
+        assert_eq!(element.rotation, Rotation::new(0.0, 0.0, 0.0, 1.0));
+        assert_eq!(element.element_type, "Other");
+        assert_eq!(element.color, Color::new(255, 255, 255, 255));
+        assert_eq!(element.face_colors, None);
+        assert_eq!(element.info.len(), 0);
+        assert_eq!(Uuid::parse_str(&element.guid).unwrap().get_version_num(), 4);
+        assert_eq!(ElementBuilder::new(3).build().guid == element.guid, false);
+    }
+
+    #[test]
+    fn test_element_builder() {
+        let element = ElementBuilder::new(0)
+            .vector(Vector::new(1.0, 2.0, 3.0))
+            .rotation(Rotation::new(0.0, 0.0, 1.0, 0.0))
+            .guid("76e051c1-1bd7-44fc-8e2e-db2b64055068")
+            .element_type("Structure")
+            .color(Color::new(255, 255, 0, 255))
+            .face_colors(vec![255, 0, 0, 255])
+            .info("Name", "Pyramid")
+            .build();
+        let mut info = HashMap::new();
+        info.insert(String::from("Name"), String::from("Pyramid"));
+        let expected = Element::new(0, Vector::new(1.0, 2.0, 3.0), Rotation::new(0.0, 0.0, 1.0, 0.0),
+                                    String::from("76e051c1-1bd7-44fc-8e2e-db2b64055068"), String::from("Structure"),
+                                    Color::new(255, 255, 0, 255), Some(vec![255, 0, 0, 255]), info);
+        assert_eq!(element, expected);
+    }
+
+    #[test]
+    fn test_file_builder() {
+        let mut builder = FileBuilder::new();
+        builder.info("Author", "John Doe").info("Date", "28.09.1999");
+        let box_id = builder.add_mesh(cuboid(7, 1.0, 1.0, 1.0));
+        let pyramid_id = builder.add_mesh(pyramid(7, 1.0, 1.0, 1.0));
+        assert_eq!(box_id, 0);
+        assert_eq!(pyramid_id, 1);
+        for x in 0..3 {
+            builder.add_element(ElementBuilder::new(box_id).vector(Vector::new(x as f64, 0.0, 0.0)).build());
+        }
+        builder.add_element(ElementBuilder::new(pyramid_id).build());
+        let file = builder.build();
+
+        assert_eq!(file.schema_version, "1.0.0");
+        assert_eq!(file.info.len(), 2);
+        assert_eq!(file.meshes.len(), 2);
+        assert_eq!(file.meshes[1].mesh_id, 1);
+        assert_eq!(file.elements.len(), 4);
+        assert_eq!(file.elements[2].vector, Vector::new(2.0, 0.0, 0.0));
+        assert_eq!(file.elements[3].mesh_id, 1);
+    }
+
+    #[test]
+    fn test_file_builder_schema_version() {
+        let file = FileBuilder::default().schema_version("1.1.0").info("Author", "John Doe").build();
+        assert_eq!(file.schema_version, "1.1.0");
+        assert_eq!(file.info.len(), 1);
+        assert_eq!(file.meshes.len(), 0);
+    }
+
+    #[test]
+    fn test_file_builder_build_resets() {
+        let mut builder = FileBuilder::new();
+        builder.schema_version("1.1.0").add_mesh(cuboid(0, 1.0, 1.0, 1.0));
+        assert_eq!(builder.build().meshes.len(), 1);
+        let file = builder.build();
+        assert_eq!(file.schema_version, "1.0.0");
+        assert_eq!(file.meshes.len(), 0);
+    }
+}
@@ -0,0 +1,660 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use serde::Serialize;
+use crate::element::Element;
+use crate::file::File;
+use crate::spatial::{closest_point_on_triangle, get_world_triangles, BoundingBox, Bvh, Triangle};
+use crate::vector::Vector;
+
+/// Relative tolerance used to ignore contacts lying exactly on triangle boundaries.
+const EPSILON: f64 = 1e-9;
+
+/// Represents the kind of a clash between two elements.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClashKind {
+    /// Geometries of the elements intersect each other.
+    Hard,
+    /// Geometries don't intersect, but they are closer to each other than the allowed tolerance.
+    Clearance,
+}
+
+/// Represents options of the clash detection.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ClashOptions {
+    /// The minimal allowed distance between elements.
+    /// Elements closer than that, but not intersecting, are reported as clearance clashes.
+    /// Value of 0 reports only hard clashes.
+    pub tolerance: f64,
+}
+
+impl ClashOptions {
+    /// Returns new ClashOptions
+    pub fn new(tolerance: f64) -> ClashOptions { ClashOptions { tolerance } }
+}
+
+/// Represents a clash found between two elements.
+#[derive(Serialize, Clone, Debug)]
+pub struct Clash {
+    /// The globally unique identifier of the element from the first group.
+    pub guid_a: String,
+    /// The globally unique identifier of the element from the second group.
+    pub guid_b: String,
+    /// The kind of the clash.
+    pub kind: ClashKind,
+    /// The approximate location of the clash in the world space.
+    /// For hard clashes it is the average of points where triangles cross each other, the center of
+    /// the contained element or a point of overlapping coplanar triangles. For clearance clashes
+    /// it is the point halfway between the closest points of the elements.
+    pub point: Vector,
+    /// For hard clashes the approximate penetration depth, measured as the largest distance from a vertex
+    /// of one element inside the other closed element to its surface. It is 0 when no vertex lies inside,
+    /// like for elements crossing only with edges, duplicated elements or open meshes.
+    /// For clearance clashes the distance between elements.
+    pub distance: f64,
+}
+
+impl PartialEq for Clash {
+    fn eq(&self, other: &Self) -> bool {
+        self.guid_a == other.guid_a && self.guid_b == other.guid_b && self.kind == other.kind
+            && self.point == other.point && self.distance == other.distance
+    }
+}
+
+/// Intermediate state of a clash between two elements gathered while testing their triangles.
+struct PairState {
+    crossing_points_sum: Vector,
+    crossing_points_count: usize,
+    closest_distance: f64,
+    closest_point: Vector,
+    contained_point: Option<Vector>,
+    coplanar_overlap: Option<(Vector, Vector)>,
+}
+
+impl PairState {
+    fn new() -> PairState {
+        PairState {
+            crossing_points_sum: Vector::new(0.0, 0.0, 0.0),
+            crossing_points_count: 0,
+            closest_distance: f64::INFINITY,
+            closest_point: Vector::new(0.0, 0.0, 0.0),
+            contained_point: None,
+            coplanar_overlap: None,
+        }
+    }
+}
+
+/// Returns the point where the segment crosses the interior of the triangle.
+/// Segments lying in the plane of the triangle or only touching it with an end are ignored.
+fn intersect_segment_triangle(start: &Vector, end: &Vector, vertices: &[Vector; 3]) -> Option<Vector> {
+    let direction = *end - *start;
+    let edge1 = vertices[1] - vertices[0];
+    let edge2 = vertices[2] - vertices[0];
+    let p = direction.cross(&edge2);
+    let determinant = edge1.dot(&p);
+    if determinant.abs() <= EPSILON * direction.length() * edge1.length() * edge2.length() {
+        return None;
+    }
+    let inverse = 1.0 / determinant;
+    let s = *start - vertices[0];
+    let u = s.dot(&p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(&edge1);
+    let v = direction.dot(&q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = edge2.dot(&q) * inverse;
+    if t <= EPSILON || t >= 1.0 - EPSILON {
+        return None;
+    }
+    Some(*start + direction * t)
+}
+
+/// Returns the points where edges of each triangle cross the other triangle.
+pub(crate) fn get_triangles_crossing_points(a: &[Vector; 3], b: &[Vector; 3]) -> Vec<Vector> {
+    let mut points = Vec::new();
+    for (edges_of, other) in [(a, b), (b, a)] {
+        for i in 0..3 {
+            if let Some(point) = intersect_segment_triangle(&edges_of[i], &edges_of[(i + 1) % 3], other) {
+                points.push(point);
+            }
+        }
+    }
+    points
+}
+
+/// Returns the closest points of the segments (p1, q1) and (p2, q2).
+fn closest_points_of_segments(p1: &Vector, q1: &Vector, p2: &Vector, q2: &Vector) -> (Vector, Vector) {
+    let d1 = *q1 - *p1;
+    let d2 = *q2 - *p2;
+    let r = *p1 - *p2;
+    let a = d1.dot(&d1);
+    let e = d2.dot(&d2);
+    let f = d2.dot(&r);
+
+    if a == 0.0 && e == 0.0 {
+        return (*p1, *p2);
+    }
+    let (s, t) = if a == 0.0 {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(&r);
+        if e == 0.0 {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(&d2);
+            let denominator = a * e - b * b;
+            let mut s = if denominator != 0.0 { ((b * f - c * e) / denominator).clamp(0.0, 1.0) } else { 0.0 };
+            let mut t = (b * s + f) / e;
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / a).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / a).clamp(0.0, 1.0);
+            }
+            (s, t)
+        }
+    };
+    (*p1 + d1 * s, *p2 + d2 * t)
+}
+
+/// Returns the closest points of two triangles, assuming they don't cross each other.
+pub(crate) fn closest_points_of_triangles(a: &[Vector; 3], b: &[Vector; 3]) -> (Vector, Vector) {
+    let mut best = (a[0], b[0]);
+    let mut best_distance = f64::INFINITY;
+    let mut consider = |pair: (Vector, Vector)| {
+        let distance = pair.0.distance_to(&pair.1);
+        if distance < best_distance {
+            best_distance = distance;
+            best = pair;
+        }
+    };
+    for vertex in a {
+        consider((*vertex, closest_point_on_triangle(vertex, &b[0], &b[1], &b[2])));
+    }
+    for vertex in b {
+        consider((closest_point_on_triangle(vertex, &a[0], &a[1], &a[2]), *vertex));
+    }
+    for i in 0..3 {
+        for j in 0..3 {
+            consider(closest_points_of_segments(&a[i], &a[(i + 1) % 3], &b[j], &b[(j + 1) % 3]));
+        }
+    }
+    best
+}
+
+/// Returns the barycentric coordinates of the point projected onto the plane of the triangle,
+/// each one belonging to the vertex opposite to the edge it is measured from.
+fn get_barycentric(point: &Vector, vertices: &[Vector; 3]) -> [f64; 3] {
+    let normal = (vertices[1] - vertices[0]).cross(&(vertices[2] - vertices[0]));
+    let area = normal.dot(&normal);
+    let mut coordinates = [0.0; 3];
+    for i in 0..3 {
+        let edge = vertices[(i + 2) % 3] - vertices[(i + 1) % 3];
+        coordinates[i] = edge.cross(&(*point - vertices[(i + 1) % 3])).dot(&normal) / area;
+    }
+    coordinates
+}
+
+/// Returns a point lying inside both triangles together with the unit normal of their plane,
+/// if the triangles lie in the same plane and their interiors overlap.
+fn get_coplanar_overlap(a: &[Vector; 3], b: &[Vector; 3]) -> Option<(Vector, Vector)> {
+    let scale = (0..3)
+        .map(|i| a[i].distance_to(&a[(i + 1) % 3]).max(b[i].distance_to(&b[(i + 1) % 3])))
+        .fold(0.0, f64::max);
+    let normal = (a[1] - a[0]).cross(&(a[2] - a[0]));
+    let normal_b = (b[1] - b[0]).cross(&(b[2] - b[0]));
+    if normal.length() <= EPSILON * scale * scale || normal_b.length() <= EPSILON * scale * scale {
+        return None;
+    }
+    let normal = normal.normalize();
+    if b.iter().any(|vertex| (*vertex - a[0]).dot(&normal).abs() > EPSILON * scale) {
+        return None;
+    }
+
+    // Corners of the overlap: vertices inside the other triangle and crossings of edges.
+    let mut corners = Vec::new();
+    for (vertices, other) in [(a, b), (b, a)] {
+        for vertex in vertices {
+            if get_barycentric(vertex, other).iter().all(|coordinate| *coordinate >= -EPSILON) {
+                corners.push(*vertex);
+            }
+        }
+    }
+    for i in 0..3 {
+        for j in 0..3 {
+            let (point_a, point_b) = closest_points_of_segments(&a[i], &a[(i + 1) % 3], &b[j], &b[(j + 1) % 3]);
+            if point_a.distance_to(&point_b) <= EPSILON * scale {
+                corners.push(point_a);
+            }
+        }
+    }
+    if corners.len() < 3 {
+        return None;
+    }
+    let center = corners.iter().fold(Vector::new(0.0, 0.0, 0.0), |sum, corner| sum + *corner) * (1.0 / corners.len() as f64);
+    // The center of the corners lies strictly inside both triangles only if the overlap has an area.
+    let is_strictly_inside = |vertices: &[Vector; 3]| get_barycentric(&center, vertices).iter().all(|coordinate| *coordinate > EPSILON);
+    if is_strictly_inside(a) && is_strictly_inside(b) { Some((center, normal)) } else { None }
+}
+
+/// Returns the distance from the point to the closest triangle of the element at the given position.
+fn get_distance_to_element(bvh: &Bvh, point: &Vector, element_index: usize) -> f64 {
+    let closest = Cell::new(f64::INFINITY);
+    bvh.visit_filtered(|bounds| bounds.distance_to_point(point) < closest.get(), |triangle| {
+        if triangle.element_index == element_index {
+            let [a, b, c] = triangle.vertices;
+            closest.set(closest.get().min(closest_point_on_triangle(point, &a, &b, &c).distance_to(point)));
+        }
+    });
+    closest.get()
+}
+
+/// World-space geometry of the elements tested for clashes, prepared on demand.
+struct ElementGeometry<'a> {
+    file: &'a File,
+    mesh_positions: HashMap<i32, usize>,
+    closed: HashMap<usize, bool>,
+    vertices: HashMap<usize, Vec<Vector>>,
+}
+
+impl<'a> ElementGeometry<'a> {
+    fn new(file: &'a File) -> ElementGeometry<'a> {
+        ElementGeometry { file, mesh_positions: file.get_mesh_positions(), closed: HashMap::new(), vertices: HashMap::new() }
+    }
+
+    /// Returns true if the mesh of the element is closed, so the element encloses a volume.
+    fn is_closed(&mut self, element_index: usize) -> bool {
+        let (file, mesh_positions) = (self.file, &self.mesh_positions);
+        *self.closed.entry(element_index).or_insert_with(|| {
+            match mesh_positions.get(&file.elements[element_index].mesh_id) {
+                Some(position) => file.meshes[*position].analyze_topology().is_closed(),
+                None => false,
+            }
+        })
+    }
+
+    /// Returns world-space vertices of the element.
+    fn get_vertices(&mut self, element_index: usize) -> &[Vector] {
+        let (file, mesh_positions) = (self.file, &self.mesh_positions);
+        self.vertices.entry(element_index).or_insert_with(|| {
+            let element = &file.elements[element_index];
+            match mesh_positions.get(&element.mesh_id) {
+                Some(position) => element.get_world_vertices(&file.meshes[*position]),
+                None => Vec::new(),
+            }
+        })
+    }
+}
+
+/// Returns true if the first box lies entirely within the second one.
+fn is_box_within(inner: &BoundingBox, outer: &BoundingBox) -> bool {
+    (0..3).all(|axis| inner.min.get(axis) >= outer.min.get(axis) && inner.max.get(axis) <= outer.max.get(axis))
+}
+
+/// Returns clashes between elements accepted by the first filter and elements accepted by the second filter.
+///
+/// Triangles of both groups are tested against each other in the world space. Elements with closed
+/// meshes are also tested for containing the other element entirely, and coplanar overlapping triangles
+/// of two closed elements clash when the volumes of both elements lie on the same side of them.
+/// Coplanar overlapping triangles of open meshes always clash. An element is never tested against itself,
+/// and when two elements belong to both groups their pair is reported once.
+/// Clashes are sorted by positions of the elements in the file.
+pub fn detect<A, B>(file: &File, group_a_filter: A, group_b_filter: B, options: &ClashOptions) -> Vec<Clash>
+    where A: Fn(&Element) -> bool, B: Fn(&Element) -> bool {
+    let in_group_a: Vec<bool> = file.elements.iter().map(&group_a_filter).collect();
+    let in_group_b: Vec<bool> = file.elements.iter().map(&group_b_filter).collect();
+    let is_tested_pair = |a: usize, b: usize| a != b && !(b < a && in_group_a[b] && in_group_b[a]);
+
+    let (triangles_a, triangles_b): (Vec<Triangle>, Vec<Triangle>) = get_world_triangles(file)
+        .into_iter()
+        .filter(|triangle| in_group_a[triangle.element_index] || in_group_b[triangle.element_index])
+        .partition(|triangle| in_group_a[triangle.element_index]);
+    // Elements belonging to both groups have to be present in the second group too.
+    let triangles_b: Vec<Triangle> = triangles_a.iter()
+        .filter(|triangle| in_group_b[triangle.element_index])
+        .copied()
+        .chain(triangles_b)
+        .collect();
+    let guids: Vec<String> = file.elements.iter().map(|element| element.guid.clone()).collect();
+    let bvh_b = Bvh::from_triangles(triangles_b, guids.clone());
+
+    let tolerance = options.tolerance.max(0.0);
+    let mut pairs: HashMap<(usize, usize), PairState> = HashMap::new();
+    for triangle_a in &triangles_a {
+        let a = triangle_a.element_index;
+        let search_box = triangle_a.bounding_box().inflate(tolerance);
+        bvh_b.visit_overlapping(&search_box, |triangle_b| {
+            let b = triangle_b.element_index;
+            if !is_tested_pair(a, b) {
+                return;
+            }
+            let crossing_points = get_triangles_crossing_points(&triangle_a.vertices, &triangle_b.vertices);
+            let has_crossing = !crossing_points.is_empty();
+            let coplanar_overlap = if has_crossing { None } else { get_coplanar_overlap(&triangle_a.vertices, &triangle_b.vertices) };
+            let closest = if has_crossing || tolerance == 0.0 {
+                None
+            } else {
+                let (point_a, point_b) = closest_points_of_triangles(&triangle_a.vertices, &triangle_b.vertices);
+                let distance = point_a.distance_to(&point_b);
+                if distance < tolerance { Some((distance, (point_a + point_b) * 0.5)) } else { None }
+            };
+            if !has_crossing && closest.is_none() && coplanar_overlap.is_none() {
+                return;
+            }
+
+            let state = pairs.entry((a, b)).or_insert_with(PairState::new);
+            for point in crossing_points {
+                state.crossing_points_sum = state.crossing_points_sum + point;
+                state.crossing_points_count += 1;
+            }
+            if let Some((distance, point)) = closest {
+                if distance < state.closest_distance {
+                    state.closest_distance = distance;
+                    state.closest_point = point;
+                }
+            }
+            if state.coplanar_overlap.is_none() {
+                state.coplanar_overlap = coplanar_overlap;
+            }
+        });
+    }
+
+    let elements_count = file.elements.len();
+    let element_boxes = get_element_bounding_boxes(&triangles_a, &bvh_b, elements_count);
+    let bvh_a = Bvh::from_triangles(triangles_a, guids);
+    let bvhs: Vec<&Bvh> = (0..elements_count).map(|i| if in_group_a[i] { &bvh_a } else { &bvh_b }).collect();
+    let surface_tolerance = |a: usize, b: usize| EPSILON * element_boxes[a].union(&element_boxes[b]).size().length();
+    let mut geometry = ElementGeometry::new(file);
+
+    // Elements entirely inside closed elements don't cross them, so they are found by their bounding boxes.
+    let mut group_b_order: Vec<usize> = (0..elements_count).filter(|b| in_group_b[*b] && !element_boxes[*b].is_empty()).collect();
+    group_b_order.sort_by(|x, y| element_boxes[*x].min.x.total_cmp(&element_boxes[*y].min.x));
+    for a in (0..elements_count).filter(|a| in_group_a[*a] && !element_boxes[*a].is_empty()) {
+        for b in group_b_order.iter().copied() {
+            if element_boxes[b].min.x > element_boxes[a].max.x {
+                break;
+            }
+            if !is_tested_pair(a, b) || pairs.get(&(a, b)).is_some_and(|state| state.crossing_points_count > 0) {
+                continue;
+            }
+            for (inner, outer) in [(a, b), (b, a)] {
+                if !is_box_within(&element_boxes[inner], &element_boxes[outer]) || !geometry.is_closed(outer) {
+                    continue;
+                }
+                let tolerance = surface_tolerance(a, b);
+                let vertices = geometry.get_vertices(inner);
+                // Vertices on the surface of the outer element can't tell, the first one off the surface decides.
+                let is_inside = vertices.iter().find_map(|vertex| bvhs[outer].contains_point(vertex, outer, tolerance));
+                if is_inside == Some(true) {
+                    pairs.entry((a, b)).or_insert_with(PairState::new).contained_point = Some(element_boxes[inner].center());
+                    break;
+                }
+            }
+        }
+    }
+
+    let mut keys: Vec<(usize, usize)> = pairs.keys().copied().collect();
+    keys.sort();
+    keys.into_iter().filter_map(|(a, b)| {
+        let state = &pairs[&(a, b)];
+        let tolerance = surface_tolerance(a, b);
+        let hard_point = if state.crossing_points_count > 0 {
+            Some(state.crossing_points_sum * (1.0 / state.crossing_points_count as f64))
+        } else if state.contained_point.is_some() {
+            state.contained_point
+        } else if let Some((point, normal)) = state.coplanar_overlap {
+            let is_hard = if geometry.is_closed(a) && geometry.is_closed(b) {
+                // Probe both sides of the shared plane, touching elements have their volumes on opposite sides.
+                let offset = normal * (tolerance * 1e3);
+                [point + offset, point - offset].iter().any(|probe| {
+                    bvh_a.contains_point(probe, a, tolerance) == Some(true) && bvh_b.contains_point(probe, b, tolerance) == Some(true)
+                })
+            } else {
+                true
+            };
+            if is_hard { Some(point) } else { None }
+        } else {
+            None
+        };
+        let (kind, point, distance) = match hard_point {
+            Some(point) => {
+                let depth = get_penetration_depth(&mut geometry, &bvhs, &element_boxes, a, b, tolerance);
+                (ClashKind::Hard, point, depth)
+            }
+            None if state.closest_distance.is_finite() => {
+                (ClashKind::Clearance, state.closest_point, state.closest_distance)
+            }
+            None => return None,
+        };
+        Some(Clash {
+            guid_a: file.elements[a].guid.clone(),
+            guid_b: file.elements[b].guid.clone(),
+            kind,
+            point,
+            distance,
+        })
+    }).collect()
+}
+
+/// Returns the largest distance from a vertex of one element lying inside the other closed element
+/// to the surface of that element, 0 if no vertex lies inside.
+fn get_penetration_depth(geometry: &mut ElementGeometry, bvhs: &[&Bvh], element_boxes: &[BoundingBox], a: usize, b: usize, tolerance: f64) -> f64 {
+    let mut depth: f64 = 0.0;
+    for (inner, outer) in [(a, b), (b, a)] {
+        if !geometry.is_closed(outer) {
+            continue;
+        }
+        let outer_box = element_boxes[outer];
+        for vertex in geometry.get_vertices(inner).iter().filter(|vertex| outer_box.contains(vertex)) {
+            if bvhs[outer].contains_point(vertex, outer, tolerance) == Some(true) {
+                depth = depth.max(get_distance_to_element(bvhs[outer], vertex, outer));
+            }
+        }
+    }
+    depth
+}
+
+/// Returns world-space bounding boxes of all elements present in any of the groups.
+fn get_element_bounding_boxes(triangles_a: &[Triangle], bvh_b: &Bvh, elements_count: usize) -> Vec<BoundingBox> {
+    let mut boxes = vec![BoundingBox::empty(); elements_count];
+    for triangle in triangles_a {
+        boxes[triangle.element_index] = boxes[triangle.element_index].union(&triangle.bounding_box());
+    }
+    bvh_b.visit_filtered(|_| true, |triangle| {
+        boxes[triangle.element_index] = boxes[triangle.element_index].union(&triangle.bounding_box());
+    });
+    boxes
+}
+
+/// Returns the clashes serialized as a JSON array.
+pub fn to_json(clashes: &[Clash]) -> Result<String, serde_json::Error> { serde_json::to_string(clashes) }
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use crate::color::Color;
+    use crate::primitives::cuboid;
+    use crate::rotation::Rotation;
+    use crate::test_utils::get_cube;
+    use super::*;
+
+    fn get_cube_element(position: Vector, guid: &str, element_type: &str) -> Element {
+        Element::new(
+            0,
+            position,
+            Rotation::new(0., 0., 0., 1.),
+            String::from(guid),
+            String::from(element_type),
+            Color::new(255, 0, 0, 255),
+            None,
+            HashMap::new(),
+        )
+    }
+
+    fn get_file(elements: Vec<Element>) -> File {
+        File::new(String::from("1.0.0"), vec![get_cube(10.0)], elements, HashMap::new())
+    }
+
+    #[test]
+    fn test_detect_hard_clash() {
+        let file = get_file(vec![
+            get_cube_element(Vector::new(0.0, 0.0, 0.0), "beam", "Beam"),
+            get_cube_element(Vector::new(8.0, 3.0, 4.0), "duct", "Duct"),
+        ]);
+        let actual = detect(&file, |e| e.element_type == "Beam", |e| e.element_type == "Duct", &ClashOptions::default());
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].guid_a, "beam");
+        assert_eq!(actual[0].guid_b, "duct");
+        assert_eq!(actual[0].kind, ClashKind::Hard);
+        assert_eq!((actual[0].distance - 2.0).abs() < 1e-9, true);
+        assert_eq!(BoundingBox::new(Vector::new(8.0, 3.0, 4.0), Vector::new(10.0, 10.0, 10.0)).contains(&actual[0].point), true);
+    }
+
+    #[test]
+    fn test_detect_clearance_clash() {
+        let file = get_file(vec![
+            get_cube_element(Vector::new(0.0, 0.0, 0.0), "beam", "Beam"),
+            get_cube_element(Vector::new(10.5, 0.0, 0.0), "duct", "Duct"),
+        ]);
+        let hard_only = detect(&file, |e| e.element_type == "Beam", |e| e.element_type == "Duct", &ClashOptions::default());
+        assert_eq!(hard_only.len(), 0);
+
+        let actual = detect(&file, |e| e.element_type == "Beam", |e| e.element_type == "Duct", &ClashOptions::new(1.0));
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].kind, ClashKind::Clearance);
+        assert_eq!((actual[0].distance - 0.5).abs() < 1e-9, true);
+        assert_eq!((actual[0].point.x - 10.25).abs() < 1e-9, true);
+
+        let too_far = detect(&file, |e| e.element_type == "Beam", |e| e.element_type == "Duct", &ClashOptions::new(0.4));
+        assert_eq!(too_far.len(), 0);
+    }
+
+    #[test]
+    fn test_detect_touching_is_not_hard_clash() {
+        let file = get_file(vec![
+            get_cube_element(Vector::new(0.0, 0.0, 0.0), "wall", "Wall"),
+            get_cube_element(Vector::new(0.0, 0.0, 10.0), "slab", "Slab"),
+        ]);
+        let hard_only = detect(&file, |e| e.element_type == "Wall", |e| e.element_type == "Slab", &ClashOptions::default());
+        assert_eq!(hard_only.len(), 0);
+        let actual = detect(&file, |e| e.element_type == "Wall", |e| e.element_type == "Slab", &ClashOptions::new(0.1));
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].kind, ClashKind::Clearance);
+        assert_eq!(actual[0].distance, 0.0);
+    }
+
+    #[test]
+    fn test_detect_same_group_reports_pair_once() {
+        let file = get_file(vec![
+            get_cube_element(Vector::new(0.0, 0.0, 0.0), "a", "Brick"),
+            get_cube_element(Vector::new(5.0, 5.0, 5.0), "b", "Brick"),
+            get_cube_element(Vector::new(100.0, 0.0, 0.0), "c", "Brick"),
+        ]);
+        let actual = detect(&file, |_| true, |_| true, &ClashOptions::default());
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].guid_a, "a");
+        assert_eq!(actual[0].guid_b, "b");
+    }
+
+    #[test]
+    fn test_detect_rotated_element() {
+        let half = std::f64::consts::FRAC_PI_8;
+        let mut rotated = get_cube_element(Vector::new(12.0, 0.0, 0.0), "rotated", "Duct");
+        // Rotate by 45 degrees around z axis, so the corner at (0, 10) reaches back to x = 4.93
+        rotated.rotation = Rotation::new(0.0, 0.0, half.sin(), half.cos());
+        let file = get_file(vec![
+            get_cube_element(Vector::new(0.0, 0.0, 0.0), "beam", "Beam"),
+            rotated,
+        ]);
+        let actual = detect(&file, |e| e.element_type == "Beam", |e| e.element_type == "Duct", &ClashOptions::default());
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].kind, ClashKind::Hard);
+    }
+
+    #[test]
+    fn test_detect_duplicated_element() {
+        let file = get_file(vec![
+            get_cube_element(Vector::new(0.0, 0.0, 0.0), "beam", "Beam"),
+            get_cube_element(Vector::new(0.0, 0.0, 0.0), "copy", "Beam"),
+        ]);
+        let actual = detect(&file, |_| true, |_| true, &ClashOptions::default());
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].guid_a, "beam");
+        assert_eq!(actual[0].guid_b, "copy");
+        assert_eq!(actual[0].kind, ClashKind::Hard);
+        assert_eq!(BoundingBox::new(Vector::new(0.0, 0.0, 0.0), Vector::new(10.0, 10.0, 10.0)).contains(&actual[0].point), true);
+    }
+
+    #[test]
+    fn test_detect_contained_element() {
+        let mut outer = get_cube_element(Vector::new(0.0, 0.0, 0.0), "outer", "Wall");
+        outer.mesh_id = 1;
+        let mut inner = get_cube_element(Vector::new(0.0, 0.0, 0.4), "inner", "Pipe");
+        inner.mesh_id = 2;
+        let file = File::new(String::from("1.0.0"), vec![cuboid(1, 1.0, 1.0, 1.0), cuboid(2, 0.2, 0.2, 0.2)],
+                              vec![outer, inner], HashMap::new());
+        for (group_a, group_b) in [("Wall", "Pipe"), ("Pipe", "Wall")] {
+            let actual = detect(&file, |e| e.element_type == group_a, |e| e.element_type == group_b, &ClashOptions::default());
+            assert_eq!(actual.len(), 1);
+            assert_eq!(actual[0].kind, ClashKind::Hard);
+            assert_eq!(actual[0].point.distance_to(&Vector::new(0.0, 0.0, 0.5)) < 1e-9, true);
+            assert_eq!((actual[0].distance - 0.4).abs() < 1e-9, true);
+        }
+    }
+
+    #[test]
+    fn test_detect_open_mesh_does_not_contain() {
+        // Without the top the box encloses no volume, so the small cube inside is not a clash
+        let mut open = cuboid(1, 1.0, 1.0, 1.0);
+        open.indices.drain(6..12);
+        let mut outer = get_cube_element(Vector::new(0.0, 0.0, 0.0), "outer", "Wall");
+        outer.mesh_id = 1;
+        let mut inner = get_cube_element(Vector::new(0.0, 0.0, 0.4), "inner", "Pipe");
+        inner.mesh_id = 2;
+        let file = File::new(String::from("1.0.0"), vec![open, cuboid(2, 0.2, 0.2, 0.2)], vec![outer, inner], HashMap::new());
+        let actual = detect(&file, |e| e.element_type == "Wall", |e| e.element_type == "Pipe", &ClashOptions::default());
+        assert_eq!(actual.len(), 0);
+    }
+
+    #[test]
+    fn test_get_coplanar_overlap() {
+        let a = [Vector::new(0.0, 0.0, 0.0), Vector::new(2.0, 0.0, 0.0), Vector::new(0.0, 2.0, 0.0)];
+        let shifted = [Vector::new(1.0, 0.0, 0.0), Vector::new(3.0, 0.0, 0.0), Vector::new(1.0, 2.0, 0.0)];
+        let (point, normal) = get_coplanar_overlap(&a, &shifted).unwrap();
+        assert_eq!(get_barycentric(&point, &a).iter().all(|coordinate| *coordinate > 0.0), true);
+        assert_eq!(get_barycentric(&point, &shifted).iter().all(|coordinate| *coordinate > 0.0), true);
+        assert_eq!(normal.eq(&Vector::new(0.0, 0.0, 1.0)), true);
+
+        let sharing_edge = [Vector::new(2.0, 0.0, 0.0), Vector::new(0.0, 2.0, 0.0), Vector::new(2.0, 2.0, 0.0)];
+        assert_eq!(get_coplanar_overlap(&a, &sharing_edge).is_none(), true);
+        let raised = [Vector::new(0.0, 0.0, 1.0), Vector::new(2.0, 0.0, 1.0), Vector::new(0.0, 2.0, 1.0)];
+        assert_eq!(get_coplanar_overlap(&a, &raised).is_none(), true);
+    }
+
+    #[test]
+    fn test_closest_points_of_segments() {
+        let (a, b) = closest_points_of_segments(&Vector::new(0.0, 0.0, 0.0), &Vector::new(10.0, 0.0, 0.0),
+                                                &Vector::new(5.0, -5.0, 3.0), &Vector::new(5.0, 5.0, 3.0));
+        assert_eq!(a.eq(&Vector::new(5.0, 0.0, 0.0)), true);
+        assert_eq!(b.eq(&Vector::new(5.0, 0.0, 3.0)), true);
+    }
+
+    #[test]
+    fn test_to_json() {
+        let clashes = vec![Clash {
+            guid_a: String::from("beam"),
+            guid_b: String::from("duct"),
+            kind: ClashKind::Clearance,
+            point: Vector::new(1.0, 2.0, 3.0),
+            distance: 0.5,
+        }];
+        let actual = to_json(&clashes);
+        assert_eq!(actual.is_ok(), true);
+        assert_eq!(actual.ok().unwrap(), "[{\"guid_a\":\"beam\",\"guid_b\":\"duct\",\"kind\":\"clearance\",\"point\":{\"x\":1.0,\"y\":2.0,\"z\":3.0},\"distance\":0.5}]");
+    }
+}
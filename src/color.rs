@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 /// Represents a color using red, green, blue, and alpha (transparency) values.
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
 pub struct Color {
     /// The red component of the color.
     pub r: i32,
@@ -19,12 +19,188 @@ impl PartialEq for Color {
     }
 }
 
+/// Returns the sRGB encoded channel value in the range 0..=1 converted to linear light.
+pub fn srgb_to_linear(value: f64) -> f64 {
+    if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+}
+
+/// Returns the channel value in linear light in the range 0..=1 converted to sRGB encoding.
+pub fn linear_to_srgb(value: f64) -> f64 {
+    if value <= 0.0031308 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 }
+}
+
+/// Returns the value in the range 0..=1 as a channel value in the range 0..=255.
+fn to_channel(value: f64) -> i32 { (value.clamp(0.0, 1.0) * 255.0).round() as i32 }
+
+/// Returns the red, green and blue values in the range 0..=1 for the hue in degrees, chroma and offset
+/// shared by HSV and HSL.
+fn get_rgb_from_hue(hue: f64, chroma: f64, offset: f64) -> (f64, f64, f64) {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as i32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    (r + offset, g + offset, b + offset)
+}
+
 impl Color {
+    /// Opaque black.
+    pub const BLACK: Color = Color { r: 0, g: 0, b: 0, a: 255 };
+    /// Opaque white.
+    pub const WHITE: Color = Color { r: 255, g: 255, b: 255, a: 255 };
+    /// Opaque red.
+    pub const RED: Color = Color { r: 255, g: 0, b: 0, a: 255 };
+    /// Opaque green.
+    pub const GREEN: Color = Color { r: 0, g: 255, b: 0, a: 255 };
+    /// Opaque blue.
+    pub const BLUE: Color = Color { r: 0, g: 0, b: 255, a: 255 };
+    /// Opaque yellow.
+    pub const YELLOW: Color = Color { r: 255, g: 255, b: 0, a: 255 };
+    /// Opaque cyan.
+    pub const CYAN: Color = Color { r: 0, g: 255, b: 255, a: 255 };
+    /// Opaque magenta.
+    pub const MAGENTA: Color = Color { r: 255, g: 0, b: 255, a: 255 };
+    /// Opaque gray.
+    pub const GRAY: Color = Color { r: 128, g: 128, b: 128, a: 255 };
+    /// Fully transparent black.
+    pub const TRANSPARENT: Color = Color { r: 0, g: 0, b: 0, a: 0 };
+
     /// Returns a new Color
     pub fn new(r: i32, g: i32, b: i32, a: i32) -> Color { Color { r, g, b, a } }
+
+    /// Returns a new Color if all values are in the range 0..=255.
+    pub fn try_new(r: i32, g: i32, b: i32, a: i32) -> Option<Color> {
+        let color = Color::new(r, g, b, a);
+        if color.is_valid() { Some(color) } else { None }
+    }
+
+    /// Returns a new Color with the values clamped to the range 0..=255.
+    pub fn new_clamped(r: i32, g: i32, b: i32, a: i32) -> Color { Color::new(r, g, b, a).clamped() }
+
+    /// Returns true if all values are in the range 0..=255.
+    pub fn is_valid(&self) -> bool { [self.r, self.g, self.b, self.a].iter().all(|value| (0..=255).contains(value)) }
+
+    /// Returns the color with the values clamped to the range 0..=255.
+    pub fn clamped(&self) -> Color {
+        Color::new(self.r.clamp(0, 255), self.g.clamp(0, 255), self.b.clamp(0, 255), self.a.clamp(0, 255))
+    }
+
+    /// Returns a color from a hex string like "#RRGGBB" or "#RRGGBBAA", the "#" being optional.
+    /// Colors without alpha are opaque.
+    pub fn from_hex(hex: &str) -> Option<Color> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if !(digits.len() == 6 || digits.len() == 8) || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let values: Vec<i32> = (0..digits.len() / 2).map(|i| i32::from_str_radix(&digits[i * 2..i * 2 + 2], 16).unwrap()).collect();
+        Some(Color::new(values[0], values[1], values[2], *values.get(3).unwrap_or(&255)))
+    }
+
+    /// Returns the color as a hex string like "#RRGGBBAA", with values clamped to the range 0..=255.
+    pub fn to_hex(&self) -> String {
+        let color = self.clamped();
+        format!("#{:02X}{:02X}{:02X}{:02X}", color.r, color.g, color.b, color.a)
+    }
+
+    /// Returns a color from values in the range 0..=1, as used by graphics APIs.
+    pub fn from_rgba_f32(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color::new(to_channel(r as f64), to_channel(g as f64), to_channel(b as f64), to_channel(a as f64))
+    }
+
+    /// Returns the values of the color in the range 0..=1.
+    pub fn to_rgba_f32(&self) -> [f32; 4] {
+        let color = self.clamped();
+        [color.r as f32 / 255.0, color.g as f32 / 255.0, color.b as f32 / 255.0, color.a as f32 / 255.0]
+    }
+
+    /// Returns a color from the hue in degrees, saturation and value in the range 0..=1 and alpha in the range 0..=255.
+    pub fn from_hsv(hue: f64, saturation: f64, value: f64, a: i32) -> Color {
+        let (saturation, value) = (saturation.clamp(0.0, 1.0), value.clamp(0.0, 1.0));
+        let chroma = value * saturation;
+        let (r, g, b) = get_rgb_from_hue(hue, chroma, value - chroma);
+        Color::new(to_channel(r), to_channel(g), to_channel(b), a.clamp(0, 255))
+    }
+
+    /// Returns the hue in degrees in the range 0..360, saturation and value in the range 0..=1.
+    pub fn to_hsv(&self) -> (f64, f64, f64) {
+        let (hue, max, min) = self.get_hue_max_min();
+        let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+        (hue, saturation, max)
+    }
+
+    /// Returns a color from the hue in degrees, saturation and lightness in the range 0..=1 and alpha in the range 0..=255.
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64, a: i32) -> Color {
+        let (saturation, lightness) = (saturation.clamp(0.0, 1.0), lightness.clamp(0.0, 1.0));
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let (r, g, b) = get_rgb_from_hue(hue, chroma, lightness - chroma / 2.0);
+        Color::new(to_channel(r), to_channel(g), to_channel(b), a.clamp(0, 255))
+    }
+
+    /// Returns the hue in degrees in the range 0..360, saturation and lightness in the range 0..=1.
+    pub fn to_hsl(&self) -> (f64, f64, f64) {
+        let (hue, max, min) = self.get_hue_max_min();
+        let lightness = (max + min) / 2.0;
+        let saturation = if max - min > 0.0 { (max - min) / (1.0 - (2.0 * lightness - 1.0).abs()) } else { 0.0 };
+        (hue, saturation, lightness)
+    }
+
+    /// Returns the hue in degrees and the largest and smallest of the red, green and blue values in the range 0..=1.
+    fn get_hue_max_min(&self) -> (f64, f64, f64) {
+        let [r, g, b, _] = self.to_rgba_f32().map(|value| value as f64);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        (hue, max, min)
+    }
+
+    /// Returns the red, green and blue values in linear light and the alpha, all in the range 0..=1.
+    pub fn to_linear(&self) -> [f64; 4] {
+        let [r, g, b, a] = self.to_rgba_f32().map(|value| value as f64);
+        [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a]
+    }
+
+    /// Returns a color from red, green and blue values in linear light and the alpha, all in the range 0..=1.
+    pub fn from_linear(r: f64, g: f64, b: f64, a: f64) -> Color {
+        Color::new(to_channel(linear_to_srgb(r.clamp(0.0, 1.0))), to_channel(linear_to_srgb(g.clamp(0.0, 1.0))),
+                   to_channel(linear_to_srgb(b.clamp(0.0, 1.0))), to_channel(a))
+    }
+
+    /// Returns the color between this one at t = 0 and the other one at t = 1, with t clamped to the range 0..=1.
+    pub fn lerp(&self, other: &Color, t: f64) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: i32, b: i32| (a as f64 + (b - a) as f64 * t).round() as i32;
+        Color::new(mix(self.r, other.r), mix(self.g, other.g), mix(self.b, other.b), mix(self.a, other.a))
+    }
+
+    /// Returns the color drawn over the background, taking the alpha of both into account.
+    pub fn blend_over(&self, background: &Color) -> Color {
+        let [r, g, b, a] = self.to_rgba_f32().map(|value| value as f64);
+        let [background_r, background_g, background_b, background_a] = background.to_rgba_f32().map(|value| value as f64);
+        let alpha = a + background_a * (1.0 - a);
+        if alpha <= 0.0 {
+            return Color::TRANSPARENT;
+        }
+        let mix = |source: f64, destination: f64| to_channel((source * a + destination * background_a * (1.0 - a)) / alpha);
+        Color::new(mix(r, background_r), mix(g, background_g), mix(b, background_b), to_channel(alpha))
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use serde_json::to_string;
     use serde_json::from_str;
@@ -105,4 +281,80 @@ mod tests {
         let expected = Color::new(11, 22, 33, 44);
         assert_eq!(expected.eq(&actual), true);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_try_new_and_clamping() {
+        assert_eq!(Color::try_new(0, 128, 255, 255), Some(Color::new(0, 128, 255, 255)));
+        assert_eq!(Color::try_new(0, 256, 0, 255), None);
+        assert_eq!(Color::try_new(-1, 0, 0, 255), None);
+        assert_eq!(Color::new_clamped(-10, 300, 5, 255), Color::new(0, 255, 5, 255));
+        assert_eq!(Color::new(0, 0, 0, 999).is_valid(), false);
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(Color::from_hex("#FF8000"), Some(Color::new(255, 128, 0, 255)));
+        assert_eq!(Color::from_hex("87ceeb80"), Some(Color::new(135, 206, 235, 128)));
+        assert_eq!(Color::from_hex("#FF80"), None);
+        assert_eq!(Color::from_hex("#GG8000"), None);
+        assert_eq!(Color::from_hex("#ééé"), None);
+        assert_eq!(Color::new(135, 206, 235, 128).to_hex(), "#87CEEB80");
+        assert_eq!(Color::new(300, -5, 0, 255).to_hex(), "#FF0000FF");
+    }
+
+    #[test]
+    fn test_rgba_f32() {
+        assert_eq!(Color::from_rgba_f32(1.0, 0.5, 0.0, 2.0), Color::new(255, 128, 0, 255));
+        assert_eq!(Color::new(255, 0, 51, 0).to_rgba_f32(), [1.0, 0.0, 0.2, 0.0]);
+    }
+
+    #[test]
+    fn test_hsv() {
+        assert_eq!(Color::from_hsv(0.0, 1.0, 1.0, 255), Color::RED);
+        assert_eq!(Color::from_hsv(120.0, 1.0, 1.0, 255), Color::GREEN);
+        assert_eq!(Color::from_hsv(-120.0, 1.0, 1.0, 255), Color::BLUE);
+        assert_eq!(Color::from_hsv(60.0, 0.0, 0.5, 100), Color::new(128, 128, 128, 100));
+        assert_eq!(Color::YELLOW.to_hsv(), (60.0, 1.0, 1.0));
+        assert_eq!(Color::BLACK.to_hsv(), (0.0, 0.0, 0.0));
+        let color = Color::new(135, 206, 235, 255);
+        let (h, s, v) = color.to_hsv();
+        assert_eq!(Color::from_hsv(h, s, v, 255), color);
+    }
+
+    #[test]
+    fn test_hsl() {
+        assert_eq!(Color::from_hsl(240.0, 1.0, 0.5, 255), Color::BLUE);
+        assert_eq!(Color::from_hsl(0.0, 0.0, 1.0, 255), Color::WHITE);
+        assert_eq!(Color::CYAN.to_hsl(), (180.0, 1.0, 0.5));
+        let color = Color::new(255, 105, 180, 150);
+        let (h, s, l) = color.to_hsl();
+        assert_eq!(Color::from_hsl(h, s, l, 150), color);
+    }
+
+    #[test]
+    fn test_linear() {
+        assert_eq!(Color::WHITE.to_linear(), [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!((Color::GRAY.to_linear()[0] - 0.2158605).abs() < 1e-6, true);
+        assert_eq!(Color::from_linear(0.2158605, 0.0, 1.0, 1.0), Color::new(128, 0, 255, 255));
+        for value in 0..=255 {
+            let [r, _, _, _] = Color::new(value, 0, 0, 255).to_linear();
+            assert_eq!(Color::from_linear(r, 0.0, 0.0, 1.0).r, value);
+        }
+    }
+
+    #[test]
+    fn test_lerp() {
+        assert_eq!(Color::BLACK.lerp(&Color::WHITE, 0.5), Color::new(128, 128, 128, 255));
+        assert_eq!(Color::RED.lerp(&Color::BLUE, 0.0), Color::RED);
+        assert_eq!(Color::RED.lerp(&Color::BLUE, 2.0), Color::BLUE);
+    }
+
+    #[test]
+    fn test_blend_over() {
+        assert_eq!(Color::RED.blend_over(&Color::BLUE), Color::RED);
+        assert_eq!(Color::new(255, 0, 0, 0).blend_over(&Color::BLUE), Color::BLUE);
+        assert_eq!(Color::new(255, 255, 255, 51).blend_over(&Color::BLACK), Color::new(51, 51, 51, 255));
+        assert_eq!(Color::TRANSPARENT.blend_over(&Color::TRANSPARENT), Color::TRANSPARENT);
+        assert_eq!(Color::new(0, 0, 255, 128).blend_over(&Color::TRANSPARENT), Color::new(0, 0, 255, 128));
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use crate::element::Element;
+use crate::file::File;
+use crate::mesh::Mesh;
+use crate::spatial::BoundingBox;
+use crate::vector::Vector;
+
+/// Represents a mesh stored with single-precision coordinates relative to the origin of its file
+/// and unsigned indices, taking half the memory of a Mesh and ready to be copied into GPU buffers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompactMesh {
+    /// The identifier for the mesh.
+    pub mesh_id: i32,
+    /// The list of coordinates for the mesh vertices, relative to the origin of the file.
+    pub coordinates: Vec<f32>,
+    /// The list of indices for the mesh triangles.
+    pub indices: Vec<u32>,
+}
+
+impl CompactMesh {
+    /// Returns the number of vertices.
+    pub fn vertices_count(&self) -> usize { self.coordinates.len() / 3 }
+
+    /// Returns the number of triangles.
+    pub fn triangles_count(&self) -> usize { self.indices.len() / 3 }
+
+    /// Returns the vertex with the given index in the local space of the mesh, given the origin of the file.
+    pub fn get_vertex(&self, index: usize, origin: &Vector) -> Vector {
+        Vector::new(self.coordinates[index * 3] as f64 + origin.x, self.coordinates[index * 3 + 1] as f64 + origin.y,
+                    self.coordinates[index * 3 + 2] as f64 + origin.z)
+    }
+}
+
+/// Represents a file with meshes in the compact form. Elements are the same as in the File.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompactFile {
+    /// The schema version of the BIM file.
+    pub schema_version: String,
+    /// The offset subtracted from all mesh coordinates, so they stay small enough for single precision.
+    pub origin: Vector,
+    /// The list of meshes in the file.
+    pub meshes: Vec<CompactMesh>,
+    /// The list of elements in the file.
+    pub elements: Vec<Element>,
+    /// Additional information about the file.
+    pub info: HashMap<String, String>,
+}
+
+impl CompactFile {
+    /// Returns the compact form of the file, with the origin in the center of all mesh coordinates.
+    /// Returns None if any mesh has a negative index.
+    pub fn from_file(file: &File) -> Option<CompactFile> {
+        let mut bounds = BoundingBox::empty();
+        for mesh in &file.meshes {
+            for vertex in mesh.coordinates.chunks_exact(3).filter(|vertex| vertex.iter().all(|value| value.is_finite())) {
+                bounds.extend(&Vector::new(vertex[0], vertex[1], vertex[2]));
+            }
+        }
+        let origin = if bounds.is_empty() { Vector::new(0.0, 0.0, 0.0) } else { bounds.center() };
+        let offsets = [origin.x, origin.y, origin.z];
+
+        let mut meshes = Vec::with_capacity(file.meshes.len());
+        for mesh in &file.meshes {
+            let indices = mesh.indices.iter().map(|index| u32::try_from(*index).ok()).collect::<Option<Vec<u32>>>()?;
+            let coordinates = mesh.coordinates.iter().enumerate().map(|(i, value)| (value - offsets[i % 3]) as f32).collect();
+            meshes.push(CompactMesh { mesh_id: mesh.mesh_id, coordinates, indices });
+        }
+        Some(CompactFile { schema_version: file.schema_version.clone(), origin, meshes, elements: file.elements.clone(), info: file.info.clone() })
+    }
+
+    /// Returns the compact form of the file, or None if any coordinate would move by more than the tolerance
+    /// or any mesh has a negative index.
+    pub fn from_file_with_tolerance(file: &File, tolerance: f64) -> Option<CompactFile> {
+        let compact = CompactFile::from_file(file)?;
+        if compact.get_max_error(file) > tolerance {
+            return None;
+        }
+        Some(compact)
+    }
+
+    /// Returns the largest difference between mesh coordinates of the file and the ones stored in the compact form.
+    /// Meshes are compared by their position in the lists. Differences in the number of coordinates are infinite.
+    pub fn get_max_error(&self, file: &File) -> f64 {
+        if self.meshes.len() != file.meshes.len() {
+            return f64::INFINITY;
+        }
+        let offsets = [self.origin.x, self.origin.y, self.origin.z];
+        let mut max_error: f64 = 0.0;
+        for (compact, mesh) in self.meshes.iter().zip(&file.meshes) {
+            if compact.coordinates.len() != mesh.coordinates.len() {
+                return f64::INFINITY;
+            }
+            for (i, (value, original)) in compact.coordinates.iter().zip(&mesh.coordinates).enumerate() {
+                let error = (*value as f64 + offsets[i % 3] - original).abs();
+                if error.is_nan() {
+                    if !(value.is_nan() && original.is_nan()) {
+                        return f64::INFINITY;
+                    }
+                } else {
+                    max_error = max_error.max(error);
+                }
+            }
+        }
+        max_error
+    }
+
+    /// Returns the file with double-precision mesh coordinates, moved back by the origin.
+    pub fn to_file(&self) -> File {
+        let offsets = [self.origin.x, self.origin.y, self.origin.z];
+        let meshes = self
+            .meshes
+            .iter()
+            .map(|mesh| {
+                let coordinates = mesh.coordinates.iter().enumerate().map(|(i, value)| *value as f64 + offsets[i % 3]).collect();
+                let indices = mesh.indices.iter().map(|index| *index as i32).collect();
+                Mesh::new(mesh.mesh_id, coordinates, indices)
+            })
+            .collect();
+        File::new(self.schema_version.clone(), meshes, self.elements.clone(), self.info.clone())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use crate::builder::{ElementBuilder, FileBuilder};
+    use crate::primitives::{cuboid, icosphere};
+    use super::*;
+
+    fn get_file(offset: Vector) -> File {
+        let mut builder = FileBuilder::new();
+        builder.info("Author", "Jane Doe");
+        for mut mesh in [cuboid(0, 2.0, 4.0, 6.0), icosphere(0, 1.5, 2)] {
+            for vertex in mesh.coordinates.chunks_exact_mut(3) {
+                vertex[0] += offset.x;
+                vertex[1] += offset.y;
+                vertex[2] += offset.z;
+            }
+            let mesh_id = builder.add_mesh(mesh);
+            builder.add_element(ElementBuilder::new(mesh_id).guid(&mesh_id.to_string()).build());
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn test_from_file_and_to_file() {
+        let file = get_file(Vector::new(0.0, 0.0, 0.0));
+        let compact = CompactFile::from_file(&file).unwrap();
+        assert_eq!(compact.origin, Vector::new(0.0, 0.0, 2.25));
+        assert_eq!(compact.meshes.len(), 2);
+        assert_eq!(compact.meshes[1].mesh_id, 1);
+        assert_eq!(compact.meshes[0].vertices_count(), 8);
+        assert_eq!(compact.meshes[0].triangles_count(), 12);
+        assert_eq!(compact.meshes[0].get_vertex(0, &compact.origin), file.meshes[0].get_vertex(0));
+        assert_eq!(compact.elements, file.elements);
+
+        let restored = compact.to_file();
+        assert_eq!(restored.meshes[0].eq(&file.meshes[0]), true);
+        assert_eq!(restored.meshes[1].indices, file.meshes[1].indices);
+        assert_eq!(restored.info, file.info);
+        assert_eq!(compact.get_max_error(&file) < 1e-6, true);
+        assert_eq!(compact.get_max_error(&restored) < 1e-6, true);
+    }
+
+    #[test]
+    fn test_origin_keeps_georeferenced_precision() {
+        let file = get_file(Vector::new(512345.678, 6123456.789, 120.5));
+        let compact = CompactFile::from_file_with_tolerance(&file, 1e-5).unwrap();
+        assert_eq!((compact.origin - Vector::new(512345.678, 6123456.789, 122.75)).length() < 1e-6, true);
+        assert_eq!(compact.to_file().meshes[0].get_vertex(0).distance_to(&file.meshes[0].get_vertex(0)) < 1e-5, true);
+
+        // Without the origin, single precision alone would move vertices by decimeters
+        let naive_error = file.meshes[0].coordinates.iter().map(|value| (*value as f32 as f64 - value).abs()).fold(0.0, f64::max);
+        assert_eq!(naive_error > 1e-2, true);
+    }
+
+    #[test]
+    fn test_from_file_with_tolerance() {
+        let file = get_file(Vector::new(0.0, 0.0, 0.0));
+        assert_eq!(CompactFile::from_file_with_tolerance(&file, 1e-6).is_some(), true);
+        assert_eq!(CompactFile::from_file_with_tolerance(&file, 0.0).is_none(), true);
+
+        let mut invalid = file.clone();
+        invalid.meshes[0].indices[0] = -1;
+        assert_eq!(CompactFile::from_file(&invalid).is_none(), true);
+        assert_eq!(CompactFile::from_file_with_tolerance(&invalid, 1.0).is_none(), true);
+    }
+
+    #[test]
+    fn test_get_max_error_mismatch() {
+        let file = get_file(Vector::new(0.0, 0.0, 0.0));
+        let compact = CompactFile::from_file(&file).unwrap();
+        let mut other = file.clone();
+        other.meshes.pop();
+        assert_eq!(compact.get_max_error(&other), f64::INFINITY);
+        other = file.clone();
+        other.meshes[0].coordinates[0] += 0.5;
+        assert_eq!((compact.get_max_error(&other) - 0.5).abs() < 1e-6, true);
+    }
+
+    #[test]
+    fn test_empty_file() {
+        let file = File::new(String::from("1.0.0"), vec![], vec![], HashMap::new());
+        let compact = CompactFile::from_file(&file).unwrap();
+        assert_eq!(compact.origin, Vector::new(0.0, 0.0, 0.0));
+        assert_eq!(compact.to_file().eq(&file), true);
+    }
+}
@@ -0,0 +1,275 @@
+use std::fs;
+use std::io::{self, BufReader, BufWriter, Cursor, Read, Write};
+use std::path::Path;
+use crate::file::File;
+use crate::writer::{write_file, WriteOptions};
+
+/// Represents the compression of a file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compression {
+    /// Plain JSON.
+    None,
+    /// Gzip, used by .bim.gz files. Needs the gzip feature.
+    Gzip,
+    /// Zstandard, used by .bim.zst files. Needs the zstd feature.
+    Zstd,
+}
+
+impl Compression {
+    /// Returns the compression recognized by the magic bytes at the beginning of the data.
+    pub fn detect(bytes: &[u8]) -> Compression {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Returns the compression matching the extension of the path: .gz for gzip, .zst for zstd.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Compression {
+        match path.as_ref().extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("gz") => Compression::Gzip,
+            Some(extension) if extension.eq_ignore_ascii_case("zst") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+}
+
+/// Returns the error for the compression that is not enabled by its feature.
+#[cfg(any(not(feature = "gzip"), not(feature = "zstd")))]
+fn get_unsupported_error(feature: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, format!("reading and writing this compression needs the {} feature", feature))
+}
+
+/// Returns a reader of the decompressed data, with the compression detected by magic bytes.
+pub fn decompress<'a, R: Read + 'a>(mut reader: R) -> io::Result<Box<dyn Read + 'a>> {
+    let mut magic = [0u8; 4];
+    let mut length = 0;
+    while length < magic.len() {
+        match reader.read(&mut magic[length..]) {
+            Ok(0) => break,
+            Ok(read) => length += read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    let reader = Cursor::new(magic[..length].to_vec()).chain(reader);
+    match Compression::detect(&magic[..length]) {
+        Compression::None => Ok(Box::new(reader)),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Ok(Box::new(flate2::read::MultiGzDecoder::new(reader))),
+        #[cfg(not(feature = "gzip"))]
+        Compression::Gzip => Err(get_unsupported_error("gzip")),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Ok(Box::new(zstd::Decoder::new(reader)?)),
+        #[cfg(not(feature = "zstd"))]
+        Compression::Zstd => Err(get_unsupported_error("zstd")),
+    }
+}
+
+enum Encoder<W: Write> {
+    None(W),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<W>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::Encoder<'static, W>),
+}
+
+/// Writes data compressed with the chosen compression. Call finish to complete the compressed stream.
+pub struct CompressedWriter<W: Write> {
+    encoder: Encoder<W>,
+}
+
+impl<W: Write> CompressedWriter<W> {
+    /// Returns a new CompressedWriter, using the default level of the compression.
+    pub fn new(writer: W, compression: Compression) -> io::Result<CompressedWriter<W>> {
+        let encoder = match compression {
+            Compression::None => Encoder::None(writer),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => Encoder::Gzip(flate2::write::GzEncoder::new(writer, flate2::Compression::default())),
+            #[cfg(not(feature = "gzip"))]
+            Compression::Gzip => return Err(get_unsupported_error("gzip")),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Encoder::Zstd(zstd::Encoder::new(writer, 0)?),
+            #[cfg(not(feature = "zstd"))]
+            Compression::Zstd => return Err(get_unsupported_error("zstd")),
+        };
+        Ok(CompressedWriter { encoder })
+    }
+
+    /// Completes the compressed stream, flushes and returns the writer.
+    pub fn finish(self) -> io::Result<W> {
+        let writer: io::Result<W> = match self.encoder {
+            Encoder::None(writer) => Ok(writer),
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(encoder) => encoder.finish(),
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(encoder) => encoder.finish(),
+        };
+        let mut writer = writer?;
+        writer.flush()?;
+        Ok(writer)
+    }
+}
+
+impl<W: Write> Write for CompressedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.encoder {
+            Encoder::None(writer) => writer.write(buf),
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(encoder) => encoder.write(buf),
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.encoder {
+            Encoder::None(writer) => writer.flush(),
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(encoder) => encoder.flush(),
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+impl File {
+    /// Returns the file read from the path, decompressed if it starts with gzip or zstd magic bytes.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<File> {
+        let reader = BufReader::new(decompress(fs::File::open(path)?)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Saves the file to the path following the options, compressed when the extension is .gz or .zst.
+    pub fn save<P: AsRef<Path>>(&self, path: P, options: &WriteOptions) -> io::Result<()> {
+        let compression = Compression::from_path(&path);
+        let mut writer = CompressedWriter::new(BufWriter::new(fs::File::create(path)?), compression)?;
+        write_file(self, &mut writer, options)?;
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use std::path::PathBuf;
+    use crate::builder::{ElementBuilder, FileBuilder};
+    use crate::primitives::icosphere;
+    use crate::reader::{read_stream, Item};
+    use super::*;
+
+    fn get_file() -> File {
+        let mut builder = FileBuilder::new();
+        let mesh_id = builder.add_mesh(icosphere(0, 1.0, 2));
+        for i in 0..3 {
+            builder.add_element(ElementBuilder::new(mesh_id).guid(&i.to_string()).build());
+        }
+        builder.build()
+    }
+
+    /// Returns the file as it is read back from JSON, since parsing can change the last digit of coordinates.
+    fn get_read_back(file: &File) -> File { serde_json::from_str(&serde_json::to_string(file).unwrap()).unwrap() }
+
+    fn get_temp_path(name: &str) -> PathBuf { std::env::temp_dir().join(format!("dotbim_rust_{}_{}", std::process::id(), name)) }
+
+    fn compress(data: &[u8], compression: Compression) -> io::Result<Vec<u8>> {
+        let mut writer = CompressedWriter::new(Vec::new(), compression)?;
+        writer.write_all(data)?;
+        writer.finish()
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Compression::detect(&[0x1f, 0x8b, 0x08]), Compression::Gzip);
+        assert_eq!(Compression::detect(&[0x28, 0xb5, 0x2f, 0xfd, 0x00]), Compression::Zstd);
+        assert_eq!(Compression::detect(b"{\"schema_version\""), Compression::None);
+        assert_eq!(Compression::detect(&[0x28, 0xb5]), Compression::None);
+        assert_eq!(Compression::detect(&[]), Compression::None);
+    }
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(Compression::from_path("model.bim.gz"), Compression::Gzip);
+        assert_eq!(Compression::from_path("model.bim.ZST"), Compression::Zstd);
+        assert_eq!(Compression::from_path("model.bim"), Compression::None);
+        assert_eq!(Compression::from_path("model"), Compression::None);
+    }
+
+    #[test]
+    fn test_uncompressed() {
+        let compressed = compress(b"{}", Compression::None).unwrap();
+        assert_eq!(compressed, b"{}");
+        let mut read = String::new();
+        decompress(&compressed[..]).unwrap().read_to_string(&mut read).unwrap();
+        assert_eq!(read, "{}");
+        let mut empty = Vec::new();
+        decompress(&[][..]).unwrap().read_to_end(&mut empty).unwrap();
+        assert_eq!(empty.len(), 0);
+    }
+
+    #[test]
+    fn test_save_and_open_uncompressed() {
+        let file = get_file();
+        let path = get_temp_path("Plain.bim");
+        file.save(&path, &WriteOptions::default()).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), serde_json::to_string(&file).unwrap());
+        assert_eq!(File::open(&path).unwrap().eq(&get_read_back(&file)), true);
+
+        let mut elements = 0;
+        read_stream(decompress(fs::File::open(&path).unwrap()).unwrap(), |item| if let Item::Element(_) = item { elements += 1 }).unwrap();
+        assert_eq!(elements, 3);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn test_gzip_without_feature() {
+        assert_eq!(compress(b"{}", Compression::Gzip).unwrap_err().kind(), io::ErrorKind::Unsupported);
+        assert_eq!(decompress(&[0x1f, 0x8b, 0x08, 0x00][..]).err().unwrap().kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip() {
+        let file = get_file();
+        let json = serde_json::to_string(&file).unwrap();
+        let compressed = compress(json.as_bytes(), Compression::Gzip).unwrap();
+        assert_eq!(Compression::detect(&compressed), Compression::Gzip);
+        assert_eq!(compressed.len() * 3 < json.len(), true);
+        let mut read = String::new();
+        decompress(&compressed[..]).unwrap().read_to_string(&mut read).unwrap();
+        assert_eq!(read, json);
+
+        let path = get_temp_path("Gzip.bim.gz");
+        file.save(&path, &WriteOptions::default()).unwrap();
+        assert_eq!(Compression::detect(&fs::read(&path).unwrap()), Compression::Gzip);
+        assert_eq!(File::open(&path).unwrap().eq(&get_read_back(&file)), true);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    #[test]
+    fn test_zstd_without_feature() {
+        assert_eq!(compress(b"{}", Compression::Zstd).unwrap_err().kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd() {
+        let file = get_file();
+        let json = serde_json::to_string(&file).unwrap();
+        let compressed = compress(json.as_bytes(), Compression::Zstd).unwrap();
+        assert_eq!(Compression::detect(&compressed), Compression::Zstd);
+        assert_eq!(compressed.len() * 3 < json.len(), true);
+
+        // Detection does not rely on the extension
+        let path = get_temp_path("Zstd.bim");
+        fs::write(&path, &compressed).unwrap();
+        assert_eq!(File::open(&path).unwrap().eq(&get_read_back(&file)), true);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -1,11 +1,12 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use crate::color::Color;
+use crate::mesh::Mesh;
 use crate::rotation::Rotation;
 use crate::vector::Vector;
 
 /// Represents a mesh object in three-dimensional space.
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Element {
     /// The identifier of the mesh associated with the element.
     pub mesh_id: i32,
@@ -33,6 +34,7 @@ pub struct Element {
 }
 
 impl PartialEq for Element {
+    #[allow(clippy::unnecessary_unwrap)]
     fn eq(&self, other: &Self) -> bool {
         if self.mesh_id != other.mesh_id {
             return false;
@@ -81,12 +83,94 @@ impl PartialEq for Element {
 
 impl Element {
     /// Returns a new Element
+    #[allow(clippy::too_many_arguments)]
     pub fn new(mesh_id: i32, vector: Vector, rotation: Rotation, guid: String, element_type: String,
                color: Color, face_colors: Option<Vec<i32>>, info: HashMap<String, String>)
         -> Element { Element { mesh_id, vector, rotation, guid, element_type, color, face_colors, info } }
+
+    /// Returns the given point from the local space of the mesh transformed to the world space,
+    /// by applying the rotation and then the position of the element.
+    pub fn transform_point(&self, point: &Vector) -> Vector { self.rotation.rotate_vector(point) + self.vector }
+
+    /// Returns the given point from the world space transformed to the local space of the mesh.
+    pub fn inverse_transform_point(&self, point: &Vector) -> Vector { self.rotation.inverse().rotate_vector(&(*point - self.vector)) }
+
+    /// Returns all vertices of the given mesh transformed to the world space.
+    pub fn get_world_vertices(&self, mesh: &Mesh) -> Vec<Vector> {
+        (0..mesh.vertices_count()).map(|i| self.transform_point(&mesh.get_vertex(i))).collect()
+    }
+
+    /// Returns the face color of the triangle with the given index, if the element has one for it.
+    pub fn face_color(&self, index: usize) -> Option<Color> {
+        let face_colors = self.face_colors.as_ref()?;
+        let rgba = face_colors.get(index * 4..index * 4 + 4)?;
+        Some(Color::new(rgba[0], rgba[1], rgba[2], rgba[3]))
+    }
+
+    /// Returns the color the triangle with the given index is displayed with:
+    /// its face color, or the color of the element if it has none.
+    pub fn triangle_color(&self, index: usize) -> Color { self.face_color(index).unwrap_or(self.color) }
+
+    /// Sets the face color of the triangle with the given index.
+    /// Face colors of triangles before it that are missing are filled with the color of the element.
+    pub fn set_face_color(&mut self, index: usize, color: Color) {
+        let element_color = [self.color.r, self.color.g, self.color.b, self.color.a];
+        let face_colors = self.face_colors.get_or_insert_with(Vec::new);
+        face_colors.truncate(face_colors.len() / 4 * 4);
+        while face_colors.len() < index * 4 + 4 {
+            face_colors.extend_from_slice(&element_color);
+        }
+        face_colors[index * 4..index * 4 + 4].copy_from_slice(&[color.r, color.g, color.b, color.a]);
+    }
+
+    /// Returns an iterator over the face colors, which is empty for elements without face colors.
+    /// Values that do not form a complete color at the end are skipped.
+    pub fn face_colors_iter(&self) -> impl Iterator<Item = Color> + '_ {
+        self.face_colors
+            .iter()
+            .flat_map(|face_colors| face_colors.chunks_exact(4))
+            .map(|rgba| Color::new(rgba[0], rgba[1], rgba[2], rgba[3]))
+    }
+
+    /// Sets the same face color for the given number of triangles.
+    pub fn set_uniform_face_colors(&mut self, color: Color, triangles_count: usize) {
+        self.set_face_colors(&vec![color; triangles_count]);
+    }
+
+    /// Returns the face colors as a list of colors, if the element has them.
+    pub fn get_face_colors(&self) -> Option<Vec<Color>> {
+        self.face_colors.as_ref().map(|_| self.face_colors_iter().collect())
+    }
+
+    /// Sets the face colors from a list of colors, one for every triangle.
+    pub fn set_face_colors(&mut self, colors: &[Color]) {
+        self.face_colors = Some(colors.iter().flat_map(|color| [color.r, color.g, color.b, color.a]).collect());
+    }
+
+    /// Returns the face color shared by all triangles, if the element has face colors and all of them are equal.
+    pub fn get_uniform_face_color(&self) -> Option<Color> {
+        let mut colors = self.face_colors_iter();
+        let first = colors.next()?;
+        if colors.all(|color| color == first) { Some(first) } else { None }
+    }
+
+    /// Replaces face colors that are all equal with the color of the element.
+    /// Returns true if the face colors were removed. Triangles without a face color
+    /// are not taken into account, see File::collapse_face_colors for a check against the mesh.
+    pub fn collapse_face_colors(&mut self) -> bool {
+        match self.get_uniform_face_color() {
+            Some(color) => {
+                self.color = color;
+                self.face_colors = None;
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use serde_json::to_string;
     use serde_json::from_str;
@@ -543,6 +627,40 @@ mod tests {
         assert_eq!(b.eq(&a), true);
     }
 
+    #[test]
+    fn test_transform_point() {
+        let half = std::f64::consts::FRAC_PI_4;
+        let mut element = get_blue_test_element();
+        element.vector = Vector::new(10.0, 20.0, 30.0);
+        element.rotation = Rotation::new(0.0, 0.0, half.sin(), half.cos());
+        let actual = element.transform_point(&Vector::new(1.0, 0.0, 2.0));
+        assert_eq!((actual.x - 10.0).abs() < 1e-12, true);
+        assert_eq!((actual.y - 21.0).abs() < 1e-12, true);
+        assert_eq!((actual.z - 32.0).abs() < 1e-12, true);
+    }
+
+    #[test]
+    fn test_inverse_transform_point() {
+        let mut element = get_blue_test_element();
+        element.vector = Vector::new(10.0, 20.0, 30.0);
+        element.rotation = Rotation::new(0.1, 0.7, -0.3, 0.6);
+        let point = Vector::new(1.0, 0.0, 2.0);
+        let actual = element.inverse_transform_point(&element.transform_point(&point));
+        assert_eq!(actual.distance_to(&point) < 1e-12, true);
+    }
+
+    #[test]
+    fn test_get_world_vertices() {
+        let mut element = get_blue_test_element();
+        element.vector = Vector::new(10.0, 20.0, 30.0);
+        element.rotation = Rotation::new(0.0, 0.0, 0.0, 1.0);
+        let mesh = Mesh::new(4, vec![0.0, 0.0, 0.0, 1.0, 2.0, 3.0], vec![]);
+        let actual = element.get_world_vertices(&mesh);
+        assert_eq!(actual.len(), 2);
+        assert_eq!(Vector::new(10.0, 20.0, 30.0).eq(&actual[0]), true);
+        assert_eq!(Vector::new(11.0, 22.0, 33.0).eq(&actual[1]), true);
+    }
+
     #[test]
     fn test_to_json_without_face_colors() {
         let input = get_blue_test_element();
@@ -582,4 +700,75 @@ mod tests {
         let expected = get_face_colored_test_element();
         assert_eq!(expected.eq(&actual), true);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_face_color() {
+        let element = get_face_colored_test_element();
+        assert_eq!(element.face_color(0), Some(Color::new(255, 105, 180, 150)));
+        assert_eq!(element.face_color(11), Some(Color::new(255, 255, 255, 255)));
+        assert_eq!(element.face_color(12), None);
+        assert_eq!(get_blue_test_element().face_color(0), None);
+        assert_eq!(element.triangle_color(3), Color::new(0, 0, 0, 255));
+        assert_eq!(element.triangle_color(12), Color::new(0, 0, 255, 0));
+    }
+
+    #[test]
+    fn test_set_face_color() {
+        let mut element = get_blue_test_element();
+        element.set_face_color(2, Color::new(255, 0, 0, 255));
+        assert_eq!(element.face_colors, Some(vec![0, 0, 255, 0, 0, 0, 255, 0, 255, 0, 0, 255]));
+        element.set_face_color(0, Color::new(1, 2, 3, 4));
+        assert_eq!(element.face_color(0), Some(Color::new(1, 2, 3, 4)));
+        assert_eq!(element.face_colors.as_ref().unwrap().len(), 12);
+
+        element.face_colors = Some(vec![1, 2, 3, 4, 5, 6]);
+        element.set_face_color(1, Color::new(7, 8, 9, 10));
+        assert_eq!(element.face_colors, Some(vec![1, 2, 3, 4, 7, 8, 9, 10]));
+    }
+
+    #[test]
+    fn test_face_colors_iter() {
+        let element = get_face_colored_test_element();
+        let colors: Vec<Color> = element.face_colors_iter().collect();
+        assert_eq!(colors.len(), 12);
+        assert_eq!(colors[4], Color::new(243, 229, 171, 255));
+        assert_eq!(get_blue_test_element().face_colors_iter().count(), 0);
+    }
+
+    #[test]
+    fn test_get_and_set_face_colors() {
+        let element = get_face_colored_test_element();
+        let colors = element.get_face_colors().unwrap();
+        let mut other = get_blue_test_element();
+        assert_eq!(other.get_face_colors(), None);
+        other.set_face_colors(&colors);
+        assert_eq!(other.face_colors, element.face_colors);
+
+        other.set_face_colors(&[]);
+        assert_eq!(other.get_face_colors(), Some(vec![]));
+    }
+
+    #[test]
+    fn test_set_uniform_face_colors() {
+        let mut element = get_blue_test_element();
+        element.set_uniform_face_colors(Color::new(10, 20, 30, 255), 3);
+        assert_eq!(element.face_colors, Some(vec![10, 20, 30, 255, 10, 20, 30, 255, 10, 20, 30, 255]));
+    }
+
+    #[test]
+    fn test_collapse_face_colors() {
+        let mut element = get_blue_test_element();
+        assert_eq!(element.collapse_face_colors(), false);
+
+        element.set_uniform_face_colors(Color::new(10, 20, 30, 255), 3);
+        assert_eq!(element.get_uniform_face_color(), Some(Color::new(10, 20, 30, 255)));
+        assert_eq!(element.collapse_face_colors(), true);
+        assert_eq!(element.face_colors, None);
+        assert_eq!(element.color, Color::new(10, 20, 30, 255));
+
+        let mut colored = get_face_colored_test_element();
+        assert_eq!(colored.get_uniform_face_color(), None);
+        assert_eq!(colored.collapse_face_colors(), false);
+        assert_eq!(colored.face_colors.is_some(), true);
+    }
+}
@@ -0,0 +1,442 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use crate::color::Color;
+use crate::file::File;
+use crate::section::{get_point_key, SectionPlane};
+use crate::spatial::get_world_triangles;
+use crate::vector::Vector;
+
+/// Represents the direction from which the orthographic view is drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum View {
+    /// Plan view, looking down along negative z axis, with y axis pointing up the drawing.
+    Top,
+    /// Front elevation, looking along positive y axis, with z axis pointing up the drawing.
+    Front,
+    /// Side elevation, looking along negative x axis, with z axis pointing up the drawing.
+    Side,
+    /// View looking along the given direction, with z axis pointing up the drawing when possible.
+    Direction(Vector),
+}
+
+/// Represents the way faces of elements are drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Style {
+    /// Faces are filled with colors of elements or their face colors.
+    Shaded,
+    /// Faces are filled with white and only their outlines are drawn, so edges behind them are hidden.
+    HiddenLine,
+}
+
+/// Represents options of the SVG export.
+#[derive(Clone, Copy, Debug)]
+pub struct SvgOptions {
+    /// The direction of the view.
+    pub view: View,
+    /// The way faces are drawn.
+    pub style: Style,
+    /// The width of the drawing in pixels, the height follows from the proportions of the model.
+    pub width: f64,
+    /// The empty space around the model in pixels.
+    pub margin: f64,
+    /// The width of drawn edges in pixels.
+    pub stroke_width: f64,
+    /// The plane cutting the model. Geometry on the side its normal points to is removed from the drawing
+    /// and cut outlines are drawn with hatching.
+    pub section: Option<SectionPlane>,
+    /// The spacing of hatch lines in pixels.
+    pub hatch_spacing: f64,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            view: View::Top,
+            style: Style::Shaded,
+            width: 800.0,
+            margin: 10.0,
+            stroke_width: 1.0,
+            section: None,
+            hatch_spacing: 4.0,
+        }
+    }
+}
+
+/// Orthographic projection of the world space onto the drawing plane.
+struct Projection {
+    right: Vector,
+    up: Vector,
+    forward: Vector,
+}
+
+impl Projection {
+    fn new(view: &View) -> Projection {
+        let (forward, up) = match view {
+            View::Top => (Vector::new(0.0, 0.0, -1.0), Vector::new(0.0, 1.0, 0.0)),
+            View::Front => (Vector::new(0.0, 1.0, 0.0), Vector::new(0.0, 0.0, 1.0)),
+            View::Side => (Vector::new(-1.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0)),
+            View::Direction(direction) => {
+                let forward = direction.normalize();
+                let z = Vector::new(0.0, 0.0, 1.0);
+                if forward.length() == 0.0 {
+                    (Vector::new(0.0, 0.0, -1.0), Vector::new(0.0, 1.0, 0.0))
+                } else if forward.cross(&z).length() < 1e-9 {
+                    (forward, Vector::new(0.0, 1.0, 0.0))
+                } else {
+                    (forward, z)
+                }
+            }
+        };
+        let right = forward.cross(&up).normalize();
+        let up = right.cross(&forward);
+        Projection { right, up, forward }
+    }
+
+    /// Returns the drawing coordinates of the point, before scaling, with y pointing up.
+    fn project(&self, point: &Vector) -> (f64, f64) { (point.dot(&self.right), point.dot(&self.up)) }
+
+    /// Returns the distance of the point from the viewer, larger values are further away.
+    fn depth(&self, point: &Vector) -> f64 { point.dot(&self.forward) }
+}
+
+/// Returns the part of the polygon lying on the negative side of the plane.
+fn clip_polygon(polygon: &[Vector], plane: &SectionPlane) -> Vec<Vector> {
+    let mut result = Vec::with_capacity(polygon.len() + 1);
+    for i in 0..polygon.len() {
+        let current = polygon[i];
+        let next = polygon[(i + 1) % polygon.len()];
+        let current_distance = (current - plane.origin).dot(&plane.normal);
+        let next_distance = (next - plane.origin).dot(&plane.normal);
+        if current_distance <= 0.0 {
+            result.push(current);
+        }
+        if (current_distance < 0.0 && next_distance > 0.0) || (current_distance > 0.0 && next_distance < 0.0) {
+            let t = current_distance / (current_distance - next_distance);
+            result.push(current + (next - current) * t);
+        }
+    }
+    result
+}
+
+/// Returns the value escaped, so it can be placed inside an XML attribute.
+fn escape_attribute(value: &str) -> String {
+    value.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Returns the color formatted as SVG color and opacity attributes with the given prefix.
+fn get_color_attributes(prefix: &str, color: &Color) -> String {
+    format!("{}=\"rgb({},{},{})\" {}-opacity=\"{}\"", prefix, color.r.clamp(0, 255), color.g.clamp(0, 255),
+            color.b.clamp(0, 255), prefix, format_number(color.a.clamp(0, 255) as f64 / 255.0))
+}
+
+/// Returns the number with up to 3 decimal places and without trailing zeros.
+fn format_number(value: f64) -> String {
+    let formatted = format!("{:.3}", value);
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    if trimmed == "-0" { String::from("0") } else { String::from(trimmed) }
+}
+
+/// Face of an element prepared for drawing.
+struct Face {
+    polygon: Vec<Vector>,
+    color: Color,
+    depth: f64,
+    /// For every side of the polygon, true if it should be outlined.
+    outlined_sides: Vec<bool>,
+}
+
+/// Returns the file drawn as an orthographic SVG view.
+///
+/// Faces are drawn using painter's algorithm: elements are ordered from the furthest one by the
+/// depth of their center, and faces inside every element by their own depth. Each element is
+/// a separate `<g>` group with `data-guid` and `data-type` attributes.
+pub fn to_svg(file: &File, options: &SvgOptions) -> String {
+    let projection = Projection::new(&options.view);
+
+    // Edges shared by two triangles facing almost the same way are not outlined in hidden line style.
+    let mut triangles_per_element: Vec<Vec<([Vector; 3], usize)>> = vec![Vec::new(); file.elements.len()];
+    for triangle in get_world_triangles(file) {
+        triangles_per_element[triangle.element_index].push((triangle.vertices, triangle.triangle_index));
+    }
+
+    let mut faces_per_element: Vec<Vec<Face>> = Vec::with_capacity(file.elements.len());
+    for (element, triangles) in file.elements.iter().zip(&triangles_per_element) {
+        let mut normals_at_edge: HashMap<([u64; 3], [u64; 3]), Vec<Vector>> = HashMap::new();
+        for (vertices, _) in triangles {
+            let normal = (vertices[1] - vertices[0]).cross(&(vertices[2] - vertices[0])).normalize();
+            for i in 0..3 {
+                normals_at_edge.entry(get_edge_key(&vertices[i], &vertices[(i + 1) % 3])).or_default().push(normal);
+            }
+        }
+
+        let mut faces = Vec::new();
+        for (vertices, triangle_index) in triangles {
+            let color = element.triangle_color(*triangle_index);
+            let sides: Vec<bool> = (0..3).map(|i| is_feature_edge(&normals_at_edge[&get_edge_key(&vertices[i], &vertices[(i + 1) % 3])])).collect();
+            let polygon = match &options.section {
+                Some(plane) => {
+                    let clipped = clip_polygon(vertices, plane);
+                    if clipped.len() < 3 {
+                        continue;
+                    }
+                    clipped
+                }
+                None => vertices.to_vec(),
+            };
+            // Sides created by clipping are outlined by the section cut, original sides keep their flag.
+            let outlined_sides = polygon.iter().enumerate().map(|(i, start)| {
+                let end = polygon[(i + 1) % polygon.len()];
+                (0..3).any(|side| sides[side] && is_on_segment(start, &vertices[side], &vertices[(side + 1) % 3])
+                    && is_on_segment(&end, &vertices[side], &vertices[(side + 1) % 3]))
+            }).collect();
+            let depth = polygon.iter().map(|point| projection.depth(point)).sum::<f64>() / polygon.len() as f64;
+            faces.push(Face { polygon, color, depth, outlined_sides });
+        }
+        faces.sort_by(|a, b| b.depth.total_cmp(&a.depth));
+        faces_per_element.push(faces);
+    }
+
+    let sections = match &options.section {
+        Some(plane) => file.section(plane.origin, plane.normal),
+        None => Vec::new(),
+    };
+    let mut sections_per_guid: HashMap<&str, usize> = HashMap::new();
+    for (index, section) in sections.iter().enumerate() {
+        sections_per_guid.insert(section.guid.as_str(), index);
+    }
+
+    // Fit the drawing to the requested width.
+    let mut min = (f64::INFINITY, f64::INFINITY);
+    let mut max = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    let mut include = |point: &Vector| {
+        let (x, y) = projection.project(point);
+        min = (min.0.min(x), min.1.min(y));
+        max = (max.0.max(x), max.1.max(y));
+    };
+    for faces in &faces_per_element {
+        for face in faces {
+            face.polygon.iter().for_each(&mut include);
+        }
+    }
+    for section in &sections {
+        for polyline in &section.polylines {
+            polyline.points.iter().for_each(&mut include);
+        }
+    }
+    if min.0 > max.0 {
+        min = (0.0, 0.0);
+        max = (0.0, 0.0);
+    }
+    let drawing_width = (options.width - 2.0 * options.margin).max(0.0);
+    let scale = if max.0 - min.0 > 0.0 { drawing_width / (max.0 - min.0) } else { 1.0 };
+    let height = (max.1 - min.1) * scale + 2.0 * options.margin;
+    let to_drawing = |point: &Vector| {
+        let (x, y) = projection.project(point);
+        ((x - min.0) * scale + options.margin, (max.1 - y) * scale + options.margin)
+    };
+    let format_points = |points: &[Vector]| {
+        points.iter()
+            .map(|point| {
+                let (x, y) = to_drawing(point);
+                format!("{},{}", format_number(x), format_number(y))
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    };
+
+    let mut svg = String::new();
+    let _ = writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+                     format_number(options.width), format_number(height), format_number(options.width), format_number(height));
+    if !sections.is_empty() {
+        let spacing = format_number(options.hatch_spacing);
+        let _ = writeln!(svg, "<defs><pattern id=\"hatch\" patternUnits=\"userSpaceOnUse\" width=\"{}\" height=\"{}\" patternTransform=\"rotate(45)\"><line x1=\"0\" y1=\"0\" x2=\"0\" y2=\"{}\" stroke=\"black\" stroke-width=\"{}\"/></pattern></defs>",
+                         spacing, spacing, spacing, format_number(options.stroke_width * 0.5));
+    }
+
+    let mut element_order: Vec<usize> = (0..file.elements.len()).collect();
+    let element_depths: Vec<f64> = faces_per_element.iter().map(|faces| {
+        if faces.is_empty() { f64::NEG_INFINITY } else { faces.iter().map(|face| face.depth).sum::<f64>() / faces.len() as f64 }
+    }).collect();
+    element_order.sort_by(|a, b| element_depths[*b].total_cmp(&element_depths[*a]).then(a.cmp(b)));
+
+    for element_index in element_order {
+        let element = &file.elements[element_index];
+        let section = sections_per_guid.get(element.guid.as_str()).map(|index| &sections[*index]);
+        let faces = &faces_per_element[element_index];
+        if faces.is_empty() && section.is_none() {
+            continue;
+        }
+        let _ = writeln!(svg, "<g data-guid=\"{}\" data-type=\"{}\">", escape_attribute(&element.guid), escape_attribute(&element.element_type));
+        for face in faces {
+            match options.style {
+                Style::Shaded => {
+                    let _ = writeln!(svg, "<polygon points=\"{}\" {} {} stroke-width=\"{}\" stroke-linejoin=\"round\"/>",
+                                     format_points(&face.polygon), get_color_attributes("fill", &face.color),
+                                     get_color_attributes("stroke", &face.color), format_number(options.stroke_width * 0.25));
+                }
+                Style::HiddenLine => {
+                    let _ = writeln!(svg, "<polygon points=\"{}\" fill=\"white\" stroke=\"white\" stroke-width=\"{}\" stroke-linejoin=\"round\"/>",
+                                     format_points(&face.polygon), format_number(options.stroke_width * 0.25));
+                    for (i, outlined) in face.outlined_sides.iter().enumerate() {
+                        if *outlined {
+                            let (x1, y1) = to_drawing(&face.polygon[i]);
+                            let (x2, y2) = to_drawing(&face.polygon[(i + 1) % face.polygon.len()]);
+                            let _ = writeln!(svg, "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\" stroke-width=\"{}\" stroke-linecap=\"round\"/>",
+                                             format_number(x1), format_number(y1), format_number(x2), format_number(y2), format_number(options.stroke_width));
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(section) = section {
+            for polyline in &section.polylines {
+                if polyline.closed {
+                    let _ = writeln!(svg, "<polygon class=\"section\" points=\"{}\" fill=\"url(#hatch)\" stroke=\"black\" stroke-width=\"{}\" stroke-linejoin=\"round\"/>",
+                                     format_points(&polyline.points), format_number(options.stroke_width * 2.0));
+                } else {
+                    let _ = writeln!(svg, "<polyline class=\"section\" points=\"{}\" fill=\"none\" stroke=\"black\" stroke-width=\"{}\" stroke-linejoin=\"round\"/>",
+                                     format_points(&polyline.points), format_number(options.stroke_width * 2.0));
+                }
+            }
+        }
+        let _ = writeln!(svg, "</g>");
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Returns the key of the edge independent of the direction it is walked.
+fn get_edge_key(a: &Vector, b: &Vector) -> ([u64; 3], [u64; 3]) {
+    let (key_a, key_b) = (get_point_key(a), get_point_key(b));
+    if key_a <= key_b { (key_a, key_b) } else { (key_b, key_a) }
+}
+
+/// Returns true if the edge is on the boundary or separates faces meeting at a visible angle.
+fn is_feature_edge(normals: &[Vector]) -> bool {
+    if normals.len() != 2 {
+        return true;
+    }
+    normals[0].dot(&normals[1]).abs() < 0.999
+}
+
+/// Returns true if the point lies on the segment (a, b), within a small tolerance.
+fn is_on_segment(point: &Vector, a: &Vector, b: &Vector) -> bool {
+    let segment = *b - *a;
+    let length = segment.length();
+    if length == 0.0 {
+        return point.distance_to(a) == 0.0;
+    }
+    segment.cross(&(*point - *a)).length() / length <= 1e-9 * length.max(1.0)
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use crate::element::Element;
+    use crate::rotation::Rotation;
+    use crate::test_utils::get_cube;
+    use super::*;
+
+    fn get_file() -> File {
+        let mut face_colored = Element::new(
+            0,
+            Vector::new(20.0, 0.0, 0.0),
+            Rotation::new(0., 0., 0., 1.),
+            String::from("4d00c967-791a-42a6-a5e8-cf05831bc11d"),
+            String::from("Column & \"Beam\""),
+            Color::new(0, 255, 0, 126),
+            None,
+            HashMap::new(),
+        );
+        face_colored.face_colors = Some([[255, 105, 180, 150]; 12].concat());
+
+        File::new(
+            String::from("1.0.0"),
+            vec![get_cube(10.0)],
+            vec![
+                Element::new(
+                    0,
+                    Vector::new(0.0, 0.0, 0.0),
+                    Rotation::new(0., 0., 0., 1.),
+                    String::from("9f61b565-06a2-4bef-8b72-f37091ab54d6"),
+                    String::from("Brick"),
+                    Color::new(255, 0, 0, 255),
+                    None,
+                    HashMap::new(),
+                ),
+                face_colored,
+            ],
+            HashMap::new(),
+        )
+    }
+
+    #[test]
+    fn test_to_svg_groups_per_element() {
+        let svg = to_svg(&get_file(), &SvgOptions::default());
+        assert_eq!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"800\" height=\"280\""), true);
+        assert_eq!(svg.contains("<g data-guid=\"9f61b565-06a2-4bef-8b72-f37091ab54d6\" data-type=\"Brick\">"), true);
+        assert_eq!(svg.contains("<g data-guid=\"4d00c967-791a-42a6-a5e8-cf05831bc11d\" data-type=\"Column &amp; &quot;Beam&quot;\">"), true);
+        assert_eq!(svg.matches("<polygon").count(), 24);
+        assert_eq!(svg.ends_with("</svg>\n"), true);
+    }
+
+    #[test]
+    fn test_to_svg_colors() {
+        let svg = to_svg(&get_file(), &SvgOptions::default());
+        assert_eq!(svg.matches("fill=\"rgb(255,0,0)\" fill-opacity=\"1\"").count(), 12);
+        assert_eq!(svg.matches("fill=\"rgb(255,105,180)\" fill-opacity=\"0.588\"").count(), 12);
+        assert_eq!(svg.contains("rgb(0,255,0)"), false);
+    }
+
+    #[test]
+    fn test_to_svg_painter_order() {
+        let svg = to_svg(&get_file(), &SvgOptions::default());
+        // In the top view the last drawn face of the cube has to be its top side
+        let first_group_end = svg.find("</g>").unwrap();
+        let last_polygon = &svg[svg[..first_group_end].rfind("<polygon").unwrap()..first_group_end];
+        assert_eq!(last_polygon.starts_with("<polygon points=\"10,270 270,10 10,10\""), true);
+        let front = to_svg(&get_file(), &SvgOptions { view: View::Front, ..SvgOptions::default() });
+        assert_eq!(front.matches("<polygon").count(), 24);
+    }
+
+    #[test]
+    fn test_to_svg_hidden_line() {
+        let svg = to_svg(&get_file(), &SvgOptions { style: Style::HiddenLine, ..SvgOptions::default() });
+        assert_eq!(svg.matches("fill=\"white\"").count(), 24);
+        // Diagonals splitting cube sides into triangles are not outlined
+        assert_eq!(svg.matches("<line").count(), 48);
+    }
+
+    #[test]
+    fn test_to_svg_section() {
+        let options = SvgOptions {
+            section: SectionPlane::new(Vector::new(0.0, 0.0, 4.0), Vector::new(0.0, 0.0, 1.0)),
+            ..SvgOptions::default()
+        };
+        let svg = to_svg(&get_file(), &options);
+        assert_eq!(svg.contains("<pattern id=\"hatch\""), true);
+        assert_eq!(svg.matches("class=\"section\"").count(), 2);
+        assert_eq!(svg.matches("fill=\"url(#hatch)\"").count(), 2);
+        // The top sides of cubes are removed, 10 triangles of each cube stay below the plane next to 2 cut outlines
+        assert_eq!(svg.matches("<polygon").count(), 22);
+    }
+
+    #[test]
+    fn test_to_svg_empty_file() {
+        let file = File::new(String::from("1.0.0"), vec![], vec![], HashMap::new());
+        let svg = to_svg(&file, &SvgOptions::default());
+        assert_eq!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"800\" height=\"20\" viewBox=\"0 0 800 20\">\n</svg>\n");
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(10.0), "10");
+        assert_eq!(format_number(1.25), "1.25");
+        assert_eq!(format_number(1.0 / 3.0), "0.333");
+        assert_eq!(format_number(-0.0001), "0");
+    }
+}
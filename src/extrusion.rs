@@ -0,0 +1,210 @@
+use crate::mesh::Mesh;
+use crate::triangulation::triangulate_polygon;
+use crate::vector::Vector;
+
+/// Returns the points reversed if the polygon is not counterclockwise.
+fn get_counterclockwise(points: &[(f64, f64)], counterclockwise: bool) -> Vec<(f64, f64)> {
+    let area: f64 = (0..points.len())
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum();
+    let mut points = points.to_vec();
+    if (area > 0.0) != counterclockwise {
+        points.reverse();
+    }
+    points
+}
+
+/// Adds quads joining two rings of vertices, each going around the loop starting at the given offsets.
+fn add_side_quads(indices: &mut Vec<i32>, first: usize, second: usize, count: usize) {
+    for i in 0..count {
+        let j = (i + 1) % count;
+        let (a, b, c, d) = ((first + i) as i32, (first + j) as i32, (second + j) as i32, (second + i) as i32);
+        indices.extend_from_slice(&[a, b, c, a, c, d]);
+    }
+}
+
+impl Mesh {
+    /// Returns a closed prism made by moving the profile with holes, lying in the XY plane,
+    /// along the direction by the given length. Loops can have any orientation.
+    /// The profile has to be a simple polygon with at least 3 points, otherwise the mesh is empty.
+    pub fn extrude(mesh_id: i32, profile: &[(f64, f64)], holes: &[Vec<(f64, f64)>], direction: Vector, length: f64) -> Mesh {
+        if profile.len() < 3 {
+            return Mesh::new(mesh_id, vec![], vec![]);
+        }
+        let extrusion = direction.normalize() * length;
+        let mut loops = vec![get_counterclockwise(profile, true)];
+        loops.extend(holes.iter().filter(|hole| hole.len() >= 3).map(|hole| get_counterclockwise(hole, false)));
+        let triangles = triangulate_polygon(&loops[0], &loops[1..]);
+
+        let points: Vec<(f64, f64)> = loops.iter().flatten().copied().collect();
+        let count = points.len();
+        let mut coordinates = Vec::with_capacity(count * 6);
+        for offset in [Vector::new(0.0, 0.0, 0.0), extrusion] {
+            for (x, y) in &points {
+                coordinates.extend_from_slice(&[x + offset.x, y + offset.y, offset.z]);
+            }
+        }
+
+        let mut indices = Vec::new();
+        for [a, b, c] in &triangles {
+            indices.extend_from_slice(&[*a as i32, *c as i32, *b as i32]);
+            indices.extend_from_slice(&[(a + count) as i32, (b + count) as i32, (c + count) as i32]);
+        }
+        let mut start = 0;
+        for loop_points in &loops {
+            add_side_quads(&mut indices, start, start + count, loop_points.len());
+            start += loop_points.len();
+        }
+        if extrusion.z < 0.0 {
+            for triangle in indices.chunks_mut(3) {
+                triangle.swap(1, 2);
+            }
+        }
+        Mesh::new(mesh_id, coordinates, indices)
+    }
+
+    /// Returns a closed mesh made by moving the profile along the path, with mitered joints.
+    ///
+    /// The profile is placed perpendicular to the first segment of the path, with its X axis horizontal
+    /// (or along the world X axis for vertical segments), and carried along the path from joint to joint.
+    /// The profile has to be a simple polygon with at least 3 points and the path needs at least
+    /// 2 distinct points, otherwise the mesh is empty.
+    pub fn sweep(mesh_id: i32, profile: &[(f64, f64)], path: &[Vector]) -> Mesh {
+        let mut points: Vec<Vector> = Vec::with_capacity(path.len());
+        for point in path {
+            if points.last().is_none_or(|last: &Vector| last.distance_to(point) > 1e-12) {
+                points.push(*point);
+            }
+        }
+        if profile.len() < 3 || points.len() < 2 {
+            return Mesh::new(mesh_id, vec![], vec![]);
+        }
+        let profile = get_counterclockwise(profile, true);
+        let tangents: Vec<Vector> = points.windows(2).map(|pair| (pair[1] - pair[0]).normalize()).collect();
+
+        // The frame is right-handed with the tangent, so counterclockwise profiles face forward.
+        let first = tangents[0];
+        let reference = if first.cross(&Vector::new(0.0, 0.0, 1.0)).length() > 1e-9 { Vector::new(0.0, 0.0, 1.0) } else { Vector::new(1.0, 0.0, 0.0) };
+        let x_axis = reference.cross(&first).normalize();
+        let y_axis = first.cross(&x_axis);
+        let mut rings: Vec<Vec<Vector>> = vec![profile.iter().map(|(x, y)| points[0] + x_axis * *x + y_axis * *y).collect()];
+
+        for joint in 1..points.len() {
+            let incoming = tangents[joint - 1];
+            let plane_normal = if joint < tangents.len() {
+                let bisector = (incoming + tangents[joint]).normalize();
+                if bisector.dot(&incoming) > 1e-9 { bisector } else { incoming }
+            } else {
+                incoming
+            };
+            let previous = &rings[joint - 1];
+            let ring = previous
+                .iter()
+                .map(|point| *point + incoming * ((points[joint] - *point).dot(&plane_normal) / incoming.dot(&plane_normal)))
+                .collect();
+            rings.push(ring);
+        }
+
+        let count = profile.len();
+        let coordinates = rings.iter().flatten().flat_map(|point| [point.x, point.y, point.z]).collect();
+        let mut indices = Vec::new();
+        let last = (rings.len() - 1) * count;
+        for [a, b, c] in triangulate_polygon(&profile, &[]) {
+            indices.extend_from_slice(&[a as i32, c as i32, b as i32]);
+            indices.extend_from_slice(&[(a + last) as i32, (b + last) as i32, (c + last) as i32]);
+        }
+        for ring in 0..rings.len() - 1 {
+            add_side_quads(&mut indices, ring * count, (ring + 1) * count, count);
+        }
+        Mesh::new(mesh_id, coordinates, indices)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    fn assert_solid(mesh: &Mesh, expected_volume: f64) {
+        let report = mesh.analyze_topology();
+        assert_eq!(report.is_valid_solid(), true);
+        assert_eq!((report.signed_volume - expected_volume).abs() < 1e-9, true);
+    }
+
+    fn get_l_profile() -> Vec<(f64, f64)> {
+        vec![(0.0, 0.0), (2.0, 0.0), (2.0, 1.0), (1.0, 1.0), (1.0, 3.0), (0.0, 3.0)]
+    }
+
+    #[test]
+    fn test_extrude_concave_profile() {
+        let mesh = Mesh::extrude(4, &get_l_profile(), &[], Vector::new(0.0, 0.0, 1.0), 5.0);
+        assert_eq!(mesh.mesh_id, 4);
+        assert_eq!(mesh.vertices_count(), 12);
+        assert_eq!(mesh.triangles_count(), 4 * 2 + 6 * 2);
+        assert_solid(&mesh, 20.0);
+    }
+
+    #[test]
+    fn test_extrude_with_holes() {
+        let outer = vec![(0.0, 0.0), (0.0, 10.0), (10.0, 10.0), (10.0, 0.0)];
+        let holes = vec![vec![(2.0, 2.0), (4.0, 2.0), (4.0, 4.0), (2.0, 4.0)], vec![(6.0, 6.0), (8.0, 6.0), (8.0, 8.0)]];
+        let mesh = Mesh::extrude(0, &outer, &holes, Vector::new(0.0, 0.0, 2.0), 0.5);
+        assert_solid(&mesh, (100.0 - 4.0 - 2.0) * 0.5);
+        assert_eq!(mesh.analyze_topology().components.len(), 1);
+    }
+
+    #[test]
+    fn test_extrude_oblique_and_downwards() {
+        let oblique = Mesh::extrude(0, &get_l_profile(), &[], Vector::new(1.0, 0.0, 1.0), 2.0_f64.sqrt());
+        assert_solid(&oblique, 4.0);
+        let downwards = Mesh::extrude(0, &get_l_profile(), &[], Vector::new(0.0, 0.0, 1.0), -3.0);
+        assert_solid(&downwards, 12.0);
+        assert_eq!(downwards.get_vertex(6).z, -3.0);
+    }
+
+    #[test]
+    fn test_extrude_invalid_profile() {
+        let mesh = Mesh::extrude(0, &[(0.0, 0.0), (1.0, 0.0)], &[], Vector::new(0.0, 0.0, 1.0), 1.0);
+        assert_eq!(mesh.vertices_count(), 0);
+        assert_eq!(mesh.triangles_count(), 0);
+    }
+
+    #[test]
+    fn test_sweep_straight() {
+        let square = vec![(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)];
+        let mesh = Mesh::sweep(0, &square, &[Vector::new(0.0, 0.0, 0.0), Vector::new(4.0, 0.0, 0.0)]);
+        assert_eq!(mesh.vertices_count(), 8);
+        assert_solid(&mesh, 4.0);
+        for index in 0..4 {
+            assert_eq!(mesh.get_vertex(index).x, 0.0);
+            assert_eq!(mesh.get_vertex(index + 4).x, 4.0);
+        }
+    }
+
+    #[test]
+    fn test_sweep_mitered_path() {
+        // With the profile centered on the path, mitered segments keep the volume of area times path length.
+        let square = vec![(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)];
+        let path = [Vector::new(0.0, 0.0, 0.0), Vector::new(4.0, 0.0, 0.0), Vector::new(4.0, 3.0, 0.0), Vector::new(4.0, 3.0, 5.0)];
+        let mesh = Mesh::sweep(0, &square, &path);
+        assert_eq!(mesh.vertices_count(), 16);
+        assert_solid(&mesh, 12.0);
+        // The first joint is cut by the plane at 45 degrees
+        let joint: Vec<Vector> = (4..8).map(|index| mesh.get_vertex(index)).collect();
+        for point in joint {
+            assert_eq!(((point.x - 4.0) + point.y).abs() < 1e-12, true);
+        }
+    }
+
+    #[test]
+    fn test_sweep_vertical_start_and_duplicate_points() {
+        let triangle = vec![(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)];
+        let path = [Vector::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 2.0)];
+        let mesh = Mesh::sweep(0, &triangle, &path);
+        assert_solid(&mesh, 1.0);
+        assert_eq!(Mesh::sweep(0, &triangle, &path[..2]).vertices_count(), 0);
+    }
+}
@@ -4,7 +4,7 @@ use crate::element::Element;
 use crate::mesh::Mesh;
 
 /// Represents a file in the BIM format.
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct File {
     /// The schema version of the BIM file.
     pub schema_version: String,
@@ -47,9 +47,35 @@ impl PartialEq for File {
 impl File {
     /// Returns a new File
     pub fn new(schema_version:String, meshes:Vec<Mesh>, elements:Vec<Element>, info:HashMap<String, String>) -> File { File {schema_version, meshes, elements, info} }
+
+    /// Returns the mesh with the given mesh_id, if the file contains one.
+    pub fn get_mesh(&self, mesh_id: i32) -> Option<&Mesh> { self.meshes.iter().find(|mesh| mesh.mesh_id == mesh_id) }
+
+    /// Returns the map from mesh_id to the position of that mesh in the meshes list.
+    pub(crate) fn get_mesh_positions(&self) -> HashMap<i32, usize> {
+        self.meshes.iter().enumerate().map(|(position, mesh)| (mesh.mesh_id, position)).collect()
+    }
+
+    /// Replaces face colors with the color of the element, for elements that have the same face color
+    /// for every triangle of their mesh. Returns the number of elements changed.
+    pub fn collapse_face_colors(&mut self) -> usize {
+        let positions = self.get_mesh_positions();
+        let mut count = 0;
+        for element in &mut self.elements {
+            let triangles_count = match positions.get(&element.mesh_id) {
+                Some(position) => self.meshes[*position].triangles_count(),
+                None => continue,
+            };
+            if element.face_colors_iter().count() == triangles_count && element.collapse_face_colors() {
+                count += 1;
+            }
+        }
+        count
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use std::fs;
     use serde_json::{from_str, from_value};
@@ -429,6 +455,30 @@ mod tests {
         assert_eq!(b.eq(&a), false);
     }
 
+    #[test]
+    fn test_get_mesh() {
+        let file = get_file_with_triangle_blue_plate();
+        assert_eq!(file.get_mesh(0).is_some(), true);
+        assert_eq!(file.get_mesh(0).unwrap().eq(&file.meshes[0]), true);
+        assert_eq!(file.get_mesh(1).is_none(), true);
+    }
+
+    #[test]
+    fn test_collapse_face_colors() {
+        let mut file = get_file_with_triangle_blue_plate();
+        let mut partial = file.elements[0].clone();
+        partial.set_uniform_face_colors(Color::new(255, 0, 0, 255), 2);
+        file.meshes[0].indices.extend_from_slice(&[0, 2, 1]);
+        file.elements[0].set_uniform_face_colors(Color::new(255, 0, 0, 255), 1);
+        file.elements.push(partial);
+
+        assert_eq!(file.collapse_face_colors(), 1);
+        assert_eq!(file.elements[0].face_colors.is_some(), true);
+        assert_eq!(file.elements[1].face_colors, None);
+        assert_eq!(file.elements[1].color, Color::new(255, 0, 0, 255));
+        assert_eq!(file.collapse_face_colors(), 0);
+    }
+
     #[test]
     fn test_to_json() {
         let input = get_file_with_triangle_blue_plate();
@@ -1,6 +1,31 @@
+
 pub mod color;
 pub mod vector;
 pub mod rotation;
 pub mod mesh;
 pub mod element;
 pub mod file;
+pub mod topology;
+pub mod repair;
+pub mod simplify;
+pub mod primitives;
+pub mod triangulation;
+pub mod extrusion;
+pub mod boolean;
+pub mod builder;
+pub mod palette;
+pub mod thematic;
+pub mod spatial;
+pub mod raycast;
+pub mod clash;
+pub mod section;
+pub mod export;
+pub mod render;
+pub mod writer;
+pub mod reader;
+pub mod compression;
+pub mod compact;
+#[cfg(feature = "tokio")]
+pub mod async_io;
+#[cfg(test)]
+mod test_utils;
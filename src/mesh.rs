@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
+use crate::vector::Vector;
 
 /// Represents a mesh object in three-dimensional space.
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Mesh {
     /// The identifier for the mesh. The value of MeshId should be greater than or equal to 0.
     pub mesh_id: i32,
@@ -40,12 +41,135 @@ impl PartialEq for Mesh {
 impl Mesh {
     /// Returns a new Mesh
     pub fn new(mesh_id: i32, coordinates: Vec<f64>, indices: Vec<i32>) -> Mesh {Mesh {mesh_id, coordinates, indices}}
+
+    /// Returns the number of vertices of the Mesh
+    pub fn vertices_count(&self) -> usize { self.coordinates.len() / 3 }
+
+    /// Returns the number of triangles of the Mesh
+    pub fn triangles_count(&self) -> usize { self.indices.len() / 3 }
+
+    /// Returns the vertex with the given index
+    pub fn get_vertex(&self, index: usize) -> Vector {
+        Vector::new(self.coordinates[index * 3], self.coordinates[index * 3 + 1], self.coordinates[index * 3 + 2])
+    }
+
+    /// Returns the vertex indices of the triangle with the given index.
+    /// None is returned if any of the indices is negative or points outside the coordinates.
+    pub fn get_triangle(&self, index: usize) -> Option<[usize; 3]> {
+        let vertices_count = self.vertices_count();
+        let mut triangle = [0; 3];
+        for (corner, vertex) in triangle.iter_mut().enumerate() {
+            let value = self.indices[index * 3 + corner];
+            if value < 0 || value as usize >= vertices_count {
+                return None;
+            }
+            *vertex = value as usize;
+        }
+        Some(triangle)
+    }
+
+    /// Returns the unit normal of every triangle, following its winding order.
+    /// Degenerate triangles and triangles with invalid indices get a zero vector.
+    pub fn face_normals(&self) -> Vec<Vector> {
+        (0..self.triangles_count()).map(|i| match self.get_triangle(i) {
+            Some([a, b, c]) => {
+                let a = self.get_vertex(a);
+                (self.get_vertex(b) - a).cross(&(self.get_vertex(c) - a)).normalize()
+            }
+            None => Vector::new(0.0, 0.0, 0.0),
+        }).collect()
+    }
+
+    /// Returns the vertex normal of every triangle corner, in the same order as indices.
+    ///
+    /// Normals of triangles sharing a vertex are averaged, weighted by the angle of each triangle at that vertex.
+    /// Triangles whose normals differ from the normal of the corner's own triangle by more than
+    /// the crease angle (in degrees) are not included, so sharp edges stay sharp.
+    pub fn vertex_normals(&self, crease_angle: f64) -> Vec<Vector> {
+        let face_normals = self.face_normals();
+        let mut corners_at_vertex: Vec<Vec<(usize, f64)>> = vec![Vec::new(); self.vertices_count()];
+        for (triangle_index, normal) in face_normals.iter().enumerate() {
+            if let Some(triangle) = self.get_triangle(triangle_index) {
+                if normal.length() == 0.0 {
+                    continue;
+                }
+                for corner in 0..3 {
+                    let vertex = self.get_vertex(triangle[corner]);
+                    let first = (self.get_vertex(triangle[(corner + 1) % 3]) - vertex).normalize();
+                    let second = (self.get_vertex(triangle[(corner + 2) % 3]) - vertex).normalize();
+                    let angle = first.dot(&second).clamp(-1.0, 1.0).acos();
+                    corners_at_vertex[triangle[corner]].push((triangle_index, angle));
+                }
+            }
+        }
+
+        let cosine_limit = crease_angle.to_radians().cos() - 1e-12;
+        let mut normals = vec![Vector::new(0.0, 0.0, 0.0); self.triangles_count() * 3];
+        for (triangle_index, own_normal) in face_normals.iter().enumerate() {
+            let triangle = match self.get_triangle(triangle_index) {
+                Some(triangle) if own_normal.length() > 0.0 => triangle,
+                _ => continue,
+            };
+            for corner in 0..3 {
+                let mut sum = Vector::new(0.0, 0.0, 0.0);
+                for (other_index, angle) in &corners_at_vertex[triangle[corner]] {
+                    let other_normal = face_normals[*other_index];
+                    if own_normal.dot(&other_normal) >= cosine_limit {
+                        sum = sum + other_normal * *angle;
+                    }
+                }
+                normals[triangle_index * 3 + corner] = if sum.length() > 0.0 { sum.normalize() } else { *own_normal };
+            }
+        }
+        normals
+    }
+
+    /// Returns a copy of the Mesh with vertices split along sharp edges, together with the normal of every vertex.
+    ///
+    /// Corners sharing a vertex and getting the same normal from vertex_normals keep sharing it,
+    /// other corners get their own copies of the vertex. Triangles with invalid indices are dropped,
+    /// the others stay in the same order, so face colors still apply to meshes without invalid triangles.
+    pub fn split_by_normals(&self, crease_angle: f64) -> (Mesh, Vec<Vector>) {
+        let corner_normals = self.vertex_normals(crease_angle);
+        let mut coordinates = Vec::with_capacity(self.coordinates.len());
+        let mut normals: Vec<Vector> = Vec::new();
+        let mut indices = Vec::with_capacity(self.indices.len());
+        // For every original vertex the new vertices created from it, with their normals.
+        let mut copies: Vec<Vec<usize>> = vec![Vec::new(); self.vertices_count()];
+        for triangle_index in 0..self.triangles_count() {
+            let triangle = match self.get_triangle(triangle_index) {
+                Some(triangle) => triangle,
+                None => continue,
+            };
+            for corner in 0..3 {
+                let original = triangle[corner];
+                let normal = corner_normals[triangle_index * 3 + corner];
+                let existing = copies[original].iter()
+                    .copied()
+                    .find(|copy| normals[*copy].distance_to(&normal) <= 1e-9);
+                let index = match existing {
+                    Some(index) => index,
+                    None => {
+                        let index = normals.len();
+                        coordinates.extend_from_slice(&self.coordinates[original * 3..original * 3 + 3]);
+                        normals.push(normal);
+                        copies[original].push(index);
+                        index
+                    }
+                };
+                indices.push(index as i32);
+            }
+        }
+        (Mesh::new(self.mesh_id, coordinates, indices), normals)
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use serde_json::from_str;
     use serde_json::to_string;
+    use crate::test_utils::get_cube;
     use super::*;
 
     #[test]
@@ -159,6 +283,121 @@ mod tests {
         assert_eq!(b.eq(&a), false);
     }
 
+    #[test]
+    fn test_counts_and_getters() {
+        let mesh = Mesh::new(12,
+                             vec![0.0, 0.0, 0.0,
+                                  10.0, 0.0, 0.0,
+                                  10.0, -15.0, 0.0],
+                             vec![0, 1, 2, 0, 1, 3, -1, 1, 2]);
+        assert_eq!(mesh.vertices_count(), 3);
+        assert_eq!(mesh.triangles_count(), 3);
+        assert_eq!(Vector::new(10.0, -15.0, 0.0).eq(&mesh.get_vertex(2)), true);
+        assert_eq!(mesh.get_triangle(0), Some([0, 1, 2]));
+        assert_eq!(mesh.get_triangle(1), None);
+        assert_eq!(mesh.get_triangle(2), None);
+    }
+
+    fn assert_vector_close(actual: &Vector, expected: &Vector) {
+        assert_eq!(actual.distance_to(expected) < 1e-9, true, "{:?} is not {:?}", actual, expected);
+    }
+
+    #[test]
+    fn test_face_normals() {
+        let mesh = Mesh::new(0,
+                             vec![0.0, 0.0, 0.0,
+                                  10.0, 0.0, 0.0,
+                                  10.0, 10.0, 0.0,
+                                  20.0, 0.0, 0.0],
+                             vec![0, 1, 2, 2, 1, 0, 0, 1, 3, 0, 1, 7]);
+        let actual = mesh.face_normals();
+        assert_eq!(actual.len(), 4);
+        assert_eq!(actual[0].eq(&Vector::new(0.0, 0.0, 1.0)), true);
+        assert_eq!(actual[1].eq(&Vector::new(0.0, 0.0, -1.0)), true);
+        assert_eq!(actual[2].eq(&Vector::new(0.0, 0.0, 0.0)), true);
+        assert_eq!(actual[3].eq(&Vector::new(0.0, 0.0, 0.0)), true);
+    }
+
+    #[test]
+    fn test_vertex_normals_sharp_cube() {
+        let mesh = get_cube(1.0);
+        let face_normals = mesh.face_normals();
+        let actual = mesh.vertex_normals(30.0);
+        assert_eq!(actual.len(), 36);
+        for (i, normal) in actual.iter().enumerate() {
+            assert_vector_close(normal, &face_normals[i / 3]);
+        }
+    }
+
+    #[test]
+    fn test_vertex_normals_smooth_cube() {
+        let mesh = get_cube(1.0);
+        let actual = mesh.vertex_normals(180.0);
+        // Every side meets a cube corner with the total angle of 90 degrees, so all of them weigh the same
+        let expected = Vector::new(1.0, 1.0, 1.0).normalize();
+        assert_vector_close(&actual[6 * 3 + 2], &expected);
+        let expected = Vector::new(-1.0, -1.0, -1.0).normalize();
+        assert_vector_close(&actual[0], &expected);
+    }
+
+    #[test]
+    fn test_vertex_normals_smooth_within_crease() {
+        // Two triangles folded by 20 degrees along the shared edge (0, 1)
+        let angle = 20.0_f64.to_radians();
+        let mesh = Mesh::new(0,
+                             vec![0.0, 0.0, 0.0,
+                                  1.0, 0.0, 0.0,
+                                  0.0, 1.0, 0.0,
+                                  0.0, -angle.cos(), angle.sin()],
+                             vec![0, 1, 2, 1, 0, 3]);
+        let smooth = mesh.vertex_normals(30.0);
+        let expected = Vector::new(0.0, (angle / 2.0).sin(), (angle / 2.0).cos());
+        assert_vector_close(&smooth[0], &expected);
+        assert_vector_close(&smooth[4], &expected);
+        // The corner at vertex 2 belongs only to the first triangle
+        assert_vector_close(&smooth[2], &Vector::new(0.0, 0.0, 1.0));
+        let sharp = mesh.vertex_normals(10.0);
+        assert_vector_close(&sharp[0], &Vector::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_split_by_normals_sharp_cube() {
+        let mesh = get_cube(1.0);
+        let (split, normals) = mesh.split_by_normals(30.0);
+        // Every corner of the cube is split into 3 vertices, one per side
+        assert_eq!(split.vertices_count(), 24);
+        assert_eq!(normals.len(), 24);
+        assert_eq!(split.triangles_count(), 12);
+        let face_normals = split.face_normals();
+        for i in 0..split.indices.len() {
+            assert_vector_close(&normals[split.indices[i] as usize], &face_normals[i / 3]);
+        }
+    }
+
+    #[test]
+    fn test_split_by_normals_smooth_cube() {
+        let mesh = get_cube(1.0);
+        let (split, normals) = mesh.split_by_normals(180.0);
+        assert_eq!(split.vertices_count(), 8);
+        assert_eq!(split.triangles_count(), 12);
+        assert_eq!(normals.len(), 8);
+        for normal in normals {
+            assert_eq!((normal.length() - 1.0).abs() < 1e-9, true);
+            assert_eq!((normal.x.abs() - normal.y.abs()).abs() < 1e-9, true);
+        }
+    }
+
+    #[test]
+    fn test_split_by_normals_drops_invalid_triangles() {
+        let mut mesh = get_cube(1.0);
+        mesh.indices.extend_from_slice(&[0, 1, 8, -1, 2, 3]);
+        let (split, normals) = mesh.split_by_normals(30.0);
+        assert_eq!(split.triangles_count(), 12);
+        assert_eq!(split.vertices_count(), 24);
+        assert_eq!(normals.len(), 24);
+        assert_eq!((0..split.triangles_count()).all(|i| split.get_triangle(i).is_some()), true);
+    }
+
     #[test]
     fn test_to_json() {
         let input = Mesh::new(12,
@@ -0,0 +1,160 @@
+use crate::color::Color;
+
+/// Colors of the categorical palette, chosen to be easy to tell apart.
+const CATEGORICAL: [Color; 10] = [
+    Color { r: 78, g: 121, b: 167, a: 255 },
+    Color { r: 242, g: 142, b: 43, a: 255 },
+    Color { r: 225, g: 87, b: 89, a: 255 },
+    Color { r: 118, g: 183, b: 178, a: 255 },
+    Color { r: 89, g: 161, b: 79, a: 255 },
+    Color { r: 237, g: 201, b: 72, a: 255 },
+    Color { r: 176, g: 122, b: 161, a: 255 },
+    Color { r: 255, g: 157, b: 167, a: 255 },
+    Color { r: 156, g: 117, b: 95, a: 255 },
+    Color { r: 186, g: 176, b: 172, a: 255 },
+];
+
+/// Returns the color of the categorical palette with the given index.
+/// The first ten colors are fixed, the following ones are spread over hues by the golden angle,
+/// alternating between lighter and darker shades, so they keep being distinct from their neighbours.
+pub fn categorical_color(index: usize) -> Color {
+    match CATEGORICAL.get(index) {
+        Some(color) => *color,
+        None => {
+            let step = index - CATEGORICAL.len();
+            let lightness = [0.45, 0.65, 0.35][step % 3];
+            Color::from_hsl(step as f64 * 137.507_764, 0.65, lightness, 255)
+        }
+    }
+}
+
+/// Returns the given number of colors of the categorical palette.
+pub fn categorical(count: usize) -> Vec<Color> { (0..count).map(categorical_color).collect() }
+
+/// Represents colors changing continuously between stops at positions in the range 0..=1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    /// The positions and colors of the stops, sorted by position.
+    pub stops: Vec<(f64, Color)>,
+}
+
+impl Gradient {
+    /// Returns a new Gradient, with the stops sorted by position.
+    pub fn new(mut stops: Vec<(f64, Color)>) -> Gradient {
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Gradient { stops }
+    }
+
+    /// Returns a gradient going from the start color to the end color.
+    pub fn between(start: Color, end: Color) -> Gradient { Gradient::new(vec![(0.0, start), (1.0, end)]) }
+
+    /// Returns the perceptually uniform gradient going from dark purple through teal to yellow.
+    pub fn viridis() -> Gradient {
+        Gradient::new(vec![
+            (0.0, Color::new(68, 1, 84, 255)),
+            (0.25, Color::new(59, 82, 139, 255)),
+            (0.5, Color::new(33, 145, 140, 255)),
+            (0.75, Color::new(94, 201, 98, 255)),
+            (1.0, Color::new(253, 231, 37, 255)),
+        ])
+    }
+
+    /// Returns the diverging gradient going from blue through white to red.
+    pub fn blue_white_red() -> Gradient {
+        Gradient::new(vec![
+            (0.0, Color::new(33, 102, 172, 255)),
+            (0.5, Color::new(247, 247, 247, 255)),
+            (1.0, Color::new(178, 24, 43, 255)),
+        ])
+    }
+
+    /// Returns the color at the given position, clamped to the range of the stops.
+    /// A gradient without stops is transparent.
+    pub fn sample(&self, t: f64) -> Color {
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Color::TRANSPARENT,
+        };
+        if t.is_nan() || t <= first.0 {
+            return first.1;
+        }
+        if t >= last.0 {
+            return last.1;
+        }
+        let next = self.stops.iter().position(|stop| stop.0 > t).unwrap();
+        let (start, end) = (self.stops[next - 1], self.stops[next]);
+        start.1.lerp(&end.1, (t - start.0) / (end.0 - start.0))
+    }
+
+    /// Returns the color for the value, placed on the gradient by the range from min to max.
+    /// If the range is empty, the color in the middle of the gradient is returned.
+    pub fn sample_range(&self, value: f64, min: f64, max: f64) -> Color {
+        if max > min { self.sample((value - min) / (max - min)) } else { self.sample(0.5) }
+    }
+
+    /// Returns the given number of colors evenly spaced along the gradient, including both ends.
+    pub fn colors(&self, count: usize) -> Vec<Color> {
+        match count {
+            0 => vec![],
+            1 => vec![self.sample(0.5)],
+            _ => (0..count).map(|i| self.sample(i as f64 / (count - 1) as f64)).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_categorical() {
+        let colors = categorical(40);
+        assert_eq!(colors.len(), 40);
+        assert_eq!(colors[0], Color::new(78, 121, 167, 255));
+        assert_eq!(colors[10], categorical_color(10));
+        for (i, a) in colors.iter().enumerate() {
+            assert_eq!(a.is_valid(), true);
+            for b in &colors[i + 1..] {
+                assert_eq!(a == b, false);
+            }
+        }
+    }
+
+    #[test]
+    fn test_gradient_sample() {
+        let gradient = Gradient::between(Color::BLACK, Color::WHITE);
+        assert_eq!(gradient.sample(0.5), Color::new(128, 128, 128, 255));
+        assert_eq!(gradient.sample(-1.0), Color::BLACK);
+        assert_eq!(gradient.sample(2.0), Color::WHITE);
+        assert_eq!(gradient.sample(f64::NAN), Color::BLACK);
+        assert_eq!(Gradient::new(vec![]).sample(0.5), Color::TRANSPARENT);
+
+        let diverging = Gradient::blue_white_red();
+        assert_eq!(diverging.sample(0.5), Color::new(247, 247, 247, 255));
+        assert_eq!(diverging.sample(0.75), Color::new(213, 136, 145, 255));
+    }
+
+    #[test]
+    fn test_gradient_unsorted_stops() {
+        let gradient = Gradient::new(vec![(1.0, Color::RED), (0.0, Color::BLUE)]);
+        assert_eq!(gradient.stops[0].1, Color::BLUE);
+        assert_eq!(gradient.sample(0.0), Color::BLUE);
+    }
+
+    #[test]
+    fn test_gradient_sample_range() {
+        let gradient = Gradient::viridis();
+        assert_eq!(gradient.sample_range(10.0, 10.0, 20.0), Color::new(68, 1, 84, 255));
+        assert_eq!(gradient.sample_range(20.0, 10.0, 20.0), Color::new(253, 231, 37, 255));
+        assert_eq!(gradient.sample_range(5.0, 5.0, 5.0), Color::new(33, 145, 140, 255));
+    }
+
+    #[test]
+    fn test_gradient_colors() {
+        let gradient = Gradient::between(Color::RED, Color::BLUE);
+        assert_eq!(gradient.colors(0).len(), 0);
+        assert_eq!(gradient.colors(1), vec![Color::new(128, 0, 128, 255)]);
+        assert_eq!(gradient.colors(3), vec![Color::RED, Color::new(128, 0, 128, 255), Color::BLUE]);
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use crate::mesh::Mesh;
+use crate::vector::Vector;
+
+/// Collects vertices and triangles of a primitive being generated.
+#[derive(Default)]
+struct Geometry {
+    coordinates: Vec<f64>,
+    indices: Vec<i32>,
+}
+
+impl Geometry {
+    /// Adds the vertex and returns its index.
+    fn vertex(&mut self, x: f64, y: f64, z: f64) -> i32 {
+        self.coordinates.extend_from_slice(&[x, y, z]);
+        (self.coordinates.len() / 3 - 1) as i32
+    }
+
+    fn triangle(&mut self, a: i32, b: i32, c: i32) { self.indices.extend_from_slice(&[a, b, c]); }
+
+    /// Adds two triangles of the quad, with corners given counterclockwise when seen from outside.
+    fn quad(&mut self, a: i32, b: i32, c: i32, d: i32) {
+        self.triangle(a, b, c);
+        self.triangle(a, c, d);
+    }
+
+    /// Adds vertices of a horizontal circle with the given number of segments and returns their indices.
+    fn ring(&mut self, radius: f64, z: f64, segments: usize) -> Vec<i32> {
+        (0..segments)
+            .map(|i| {
+                let angle = 2.0 * PI * i as f64 / segments as f64;
+                self.vertex(radius * angle.cos(), radius * angle.sin(), z)
+            })
+            .collect()
+    }
+
+    fn into_mesh(self, mesh_id: i32) -> Mesh { Mesh::new(mesh_id, self.coordinates, self.indices) }
+}
+
+/// Returns true if the size is a positive finite number.
+fn is_positive(value: f64) -> bool { value > 0.0 && value.is_finite() }
+
+/// Returns the mesh without vertices and triangles, generated for invalid parameters.
+fn get_empty(mesh_id: i32) -> Mesh { Mesh::new(mesh_id, Vec::new(), Vec::new()) }
+
+/// Returns a box with the base centered at the origin, standing on the XY plane.
+/// Returns an empty mesh if any size is not positive and finite.
+pub fn cuboid(mesh_id: i32, size_x: f64, size_y: f64, size_z: f64) -> Mesh {
+    if !is_positive(size_x) || !is_positive(size_y) || !is_positive(size_z) {
+        return get_empty(mesh_id);
+    }
+    let (x, y) = (size_x / 2.0, size_y / 2.0);
+    let mut geometry = Geometry::default();
+    let bottom = [geometry.vertex(-x, -y, 0.0), geometry.vertex(x, -y, 0.0), geometry.vertex(x, y, 0.0), geometry.vertex(-x, y, 0.0)];
+    let top = [geometry.vertex(-x, -y, size_z), geometry.vertex(x, -y, size_z), geometry.vertex(x, y, size_z), geometry.vertex(-x, y, size_z)];
+    geometry.quad(bottom[0], bottom[3], bottom[2], bottom[1]);
+    geometry.quad(top[0], top[1], top[2], top[3]);
+    for i in 0..4 {
+        let j = (i + 1) % 4;
+        geometry.quad(bottom[i], bottom[j], top[j], top[i]);
+    }
+    geometry.into_mesh(mesh_id)
+}
+
+/// Returns a cylinder around the Z axis with the base centered at the origin.
+/// The circles are approximated with the given number of segments.
+/// Returns an empty mesh if the radius or the height is not positive and finite, or there are less than 3 segments.
+pub fn cylinder(mesh_id: i32, radius: f64, height: f64, segments: usize) -> Mesh {
+    if !is_positive(radius) || !is_positive(height) || segments < 3 {
+        return get_empty(mesh_id);
+    }
+    let mut geometry = Geometry::default();
+    let bottom = geometry.ring(radius, 0.0, segments);
+    let top = geometry.ring(radius, height, segments);
+    let bottom_center = geometry.vertex(0.0, 0.0, 0.0);
+    let top_center = geometry.vertex(0.0, 0.0, height);
+    for i in 0..segments {
+        let j = (i + 1) % segments;
+        geometry.quad(bottom[i], bottom[j], top[j], top[i]);
+        geometry.triangle(top_center, top[i], top[j]);
+        geometry.triangle(bottom_center, bottom[j], bottom[i]);
+    }
+    geometry.into_mesh(mesh_id)
+}
+
+/// Returns a cone around the Z axis with the base centered at the origin and the apex above it.
+/// The base circle is approximated with the given number of segments.
+/// Returns an empty mesh if the radius or the height is not positive and finite, or there are less than 3 segments.
+pub fn cone(mesh_id: i32, radius: f64, height: f64, segments: usize) -> Mesh {
+    if !is_positive(radius) || !is_positive(height) || segments < 3 {
+        return get_empty(mesh_id);
+    }
+    let mut geometry = Geometry::default();
+    let bottom = geometry.ring(radius, 0.0, segments);
+    let bottom_center = geometry.vertex(0.0, 0.0, 0.0);
+    let apex = geometry.vertex(0.0, 0.0, height);
+    for i in 0..segments {
+        let j = (i + 1) % segments;
+        geometry.triangle(bottom[i], bottom[j], apex);
+        geometry.triangle(bottom_center, bottom[j], bottom[i]);
+    }
+    geometry.into_mesh(mesh_id)
+}
+
+/// Returns a sphere centered at the origin, made of the given number of segments around the Z axis
+/// and rings from pole to pole. Returns an empty mesh if the radius is not positive and finite,
+/// or there are less than 3 segments or less than 2 rings.
+pub fn uv_sphere(mesh_id: i32, radius: f64, segments: usize, rings: usize) -> Mesh {
+    if !is_positive(radius) || segments < 3 || rings < 2 {
+        return get_empty(mesh_id);
+    }
+    let mut geometry = Geometry::default();
+    let north = geometry.vertex(0.0, 0.0, radius);
+    let circles: Vec<Vec<i32>> = (1..rings)
+        .map(|ring| {
+            let angle = PI * ring as f64 / rings as f64;
+            geometry.ring(radius * angle.sin(), radius * angle.cos(), segments)
+        })
+        .collect();
+    let south = geometry.vertex(0.0, 0.0, -radius);
+    for i in 0..segments {
+        let j = (i + 1) % segments;
+        geometry.triangle(north, circles[0][i], circles[0][j]);
+        for ring in 0..circles.len() - 1 {
+            let (upper, lower) = (&circles[ring], &circles[ring + 1]);
+            geometry.quad(upper[i], lower[i], lower[j], upper[j]);
+        }
+        let last = &circles[circles.len() - 1];
+        geometry.triangle(last[i], south, last[j]);
+    }
+    geometry.into_mesh(mesh_id)
+}
+
+/// Returns a sphere centered at the origin, made by splitting every triangle of an icosahedron
+/// into four the given number of times. Returns an empty mesh if the radius is not positive and finite.
+pub fn icosphere(mesh_id: i32, radius: f64, subdivisions: usize) -> Mesh {
+    if !is_positive(radius) {
+        return get_empty(mesh_id);
+    }
+    let t = (1.0 + 5.0_f64.sqrt()) / 2.0;
+    let mut vertices: Vec<Vector> = [
+        (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+        (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+        (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+    ].iter().map(|(x, y, z)| Vector::new(*x, *y, *z).normalize()).collect();
+    let mut triangles: Vec<[usize; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        let mut middles: HashMap<[usize; 2], usize> = HashMap::new();
+        let mut get_middle = |a: usize, b: usize, vertices: &mut Vec<Vector>| -> usize {
+            *middles.entry([a.min(b), a.max(b)]).or_insert_with(|| {
+                vertices.push(((vertices[a] + vertices[b]) * 0.5).normalize());
+                vertices.len() - 1
+            })
+        };
+        let mut split = Vec::with_capacity(triangles.len() * 4);
+        for [a, b, c] in triangles {
+            let ab = get_middle(a, b, &mut vertices);
+            let bc = get_middle(b, c, &mut vertices);
+            let ca = get_middle(c, a, &mut vertices);
+            split.extend_from_slice(&[[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]);
+        }
+        triangles = split;
+    }
+
+    let coordinates = vertices.iter().flat_map(|vertex| [vertex.x * radius, vertex.y * radius, vertex.z * radius]).collect();
+    let indices = triangles.iter().flatten().map(|index| *index as i32).collect();
+    Mesh::new(mesh_id, coordinates, indices)
+}
+
+/// Returns a torus around the Z axis centered at the origin. The major circle is approximated with
+/// major_segments and the cross-section of the tube with minor_segments.
+/// Returns an empty mesh if the minor radius is not positive and finite, the major radius is not larger than it,
+/// which would make the surface cross itself, or there are less than 3 segments of either kind.
+pub fn torus(mesh_id: i32, major_radius: f64, minor_radius: f64, major_segments: usize, minor_segments: usize) -> Mesh {
+    if !is_positive(minor_radius) || !is_positive(major_radius) || major_radius <= minor_radius
+        || major_segments < 3 || minor_segments < 3 {
+        return get_empty(mesh_id);
+    }
+    let mut geometry = Geometry::default();
+    let circles: Vec<Vec<i32>> = (0..minor_segments)
+        .map(|j| {
+            let angle = 2.0 * PI * j as f64 / minor_segments as f64;
+            geometry.ring(major_radius + minor_radius * angle.cos(), minor_radius * angle.sin(), major_segments)
+        })
+        .collect();
+    for j in 0..minor_segments {
+        let next_j = (j + 1) % minor_segments;
+        for i in 0..major_segments {
+            let next_i = (i + 1) % major_segments;
+            geometry.quad(circles[j][i], circles[j][next_i], circles[next_j][next_i], circles[next_j][i]);
+        }
+    }
+    geometry.into_mesh(mesh_id)
+}
+
+/// Returns a wedge with the rectangular base centered at the origin, standing on the XY plane.
+/// It has the full height on the side facing negative X and slopes down to the base on the other side.
+/// Returns an empty mesh if any size is not positive and finite.
+pub fn wedge(mesh_id: i32, size_x: f64, size_y: f64, size_z: f64) -> Mesh {
+    if !is_positive(size_x) || !is_positive(size_y) || !is_positive(size_z) {
+        return get_empty(mesh_id);
+    }
+    let (x, y) = (size_x / 2.0, size_y / 2.0);
+    let mut geometry = Geometry::default();
+    let bottom = [geometry.vertex(-x, -y, 0.0), geometry.vertex(x, -y, 0.0), geometry.vertex(x, y, 0.0), geometry.vertex(-x, y, 0.0)];
+    let top = [geometry.vertex(-x, -y, size_z), geometry.vertex(-x, y, size_z)];
+    geometry.quad(bottom[0], bottom[3], bottom[2], bottom[1]);
+    geometry.quad(bottom[3], bottom[0], top[0], top[1]);
+    geometry.quad(bottom[1], bottom[2], top[1], top[0]);
+    geometry.triangle(bottom[0], bottom[1], top[0]);
+    geometry.triangle(bottom[2], bottom[3], top[1]);
+    geometry.into_mesh(mesh_id)
+}
+
+/// Returns a pyramid with the rectangular base centered at the origin and the apex above it.
+/// Returns an empty mesh if any size or the height is not positive and finite.
+pub fn pyramid(mesh_id: i32, size_x: f64, size_y: f64, height: f64) -> Mesh {
+    if !is_positive(size_x) || !is_positive(size_y) || !is_positive(height) {
+        return get_empty(mesh_id);
+    }
+    let (x, y) = (size_x / 2.0, size_y / 2.0);
+    let mut geometry = Geometry::default();
+    let bottom = [geometry.vertex(-x, -y, 0.0), geometry.vertex(x, -y, 0.0), geometry.vertex(x, y, 0.0), geometry.vertex(-x, y, 0.0)];
+    let apex = geometry.vertex(0.0, 0.0, height);
+    geometry.quad(bottom[0], bottom[3], bottom[2], bottom[1]);
+    for i in 0..4 {
+        geometry.triangle(bottom[i], bottom[(i + 1) % 4], apex);
+    }
+    geometry.into_mesh(mesh_id)
+}
+
+/// Returns a hollow cylinder around the Z axis with the base centered at the origin.
+/// The circles are approximated with the given number of segments.
+/// Returns an empty mesh if the inner radius or the height is not positive and finite, the outer radius
+/// is not larger than the inner one, or there are less than 3 segments.
+pub fn tube(mesh_id: i32, outer_radius: f64, inner_radius: f64, height: f64, segments: usize) -> Mesh {
+    if !is_positive(inner_radius) || !is_positive(outer_radius) || !is_positive(height)
+        || outer_radius <= inner_radius || segments < 3 {
+        return get_empty(mesh_id);
+    }
+    let mut geometry = Geometry::default();
+    let outer_bottom = geometry.ring(outer_radius, 0.0, segments);
+    let outer_top = geometry.ring(outer_radius, height, segments);
+    let inner_bottom = geometry.ring(inner_radius, 0.0, segments);
+    let inner_top = geometry.ring(inner_radius, height, segments);
+    for i in 0..segments {
+        let j = (i + 1) % segments;
+        geometry.quad(outer_bottom[i], outer_bottom[j], outer_top[j], outer_top[i]);
+        geometry.quad(inner_bottom[j], inner_bottom[i], inner_top[i], inner_top[j]);
+        geometry.quad(outer_top[i], outer_top[j], inner_top[j], inner_top[i]);
+        geometry.quad(outer_bottom[i], inner_bottom[i], inner_bottom[j], outer_bottom[j]);
+    }
+    geometry.into_mesh(mesh_id)
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    fn assert_solid(mesh: &Mesh, expected_volume: f64) {
+        let report = mesh.analyze_topology();
+        assert_eq!(report.is_valid_solid(), true);
+        assert_eq!(report.components.len(), 1);
+        assert_eq!((report.signed_volume - expected_volume).abs() < 1e-9 * expected_volume.max(1.0), true);
+    }
+
+    /// Returns the area of a regular polygon with the given number of sides inscribed in the circle.
+    fn get_polygon_area(radius: f64, segments: usize) -> f64 {
+        segments as f64 / 2.0 * radius * radius * (2.0 * PI / segments as f64).sin()
+    }
+
+    #[test]
+    fn test_cuboid() {
+        let mesh = cuboid(3, 2.0, 3.0, 4.0);
+        assert_eq!(mesh.mesh_id, 3);
+        assert_eq!(mesh.vertices_count(), 8);
+        assert_eq!(mesh.triangles_count(), 12);
+        assert_solid(&mesh, 24.0);
+    }
+
+    #[test]
+    fn test_cylinder() {
+        let mesh = cylinder(0, 2.0, 5.0, 16);
+        assert_eq!(mesh.triangles_count(), 64);
+        assert_solid(&mesh, get_polygon_area(2.0, 16) * 5.0);
+        assert_eq!(cylinder(0, 1.0, 1.0, 3).triangles_count(), 12);
+    }
+
+    #[test]
+    fn test_cone() {
+        let mesh = cone(0, 2.0, 6.0, 12);
+        assert_eq!(mesh.triangles_count(), 24);
+        assert_solid(&mesh, get_polygon_area(2.0, 12) * 2.0);
+    }
+
+    #[test]
+    fn test_uv_sphere() {
+        let mesh = uv_sphere(0, 1.0, 24, 12);
+        assert_eq!(mesh.vertices_count(), 24 * 11 + 2);
+        assert_eq!(mesh.triangles_count(), 24 * 2 + 24 * 10 * 2);
+        let volume = mesh.analyze_topology().signed_volume;
+        assert_solid(&mesh, volume);
+        assert_eq!(volume < 4.0 / 3.0 * PI && volume > 4.0, true);
+        let minimal = uv_sphere(0, 1.0, 3, 2);
+        assert_solid(&minimal, minimal.analyze_topology().signed_volume);
+        assert_eq!(minimal.triangles_count(), 6);
+    }
+
+    #[test]
+    fn test_icosphere() {
+        let mesh = icosphere(0, 2.0, 2);
+        assert_eq!(mesh.triangles_count(), 320);
+        assert_eq!(mesh.vertices_count(), 162);
+        for index in 0..mesh.vertices_count() {
+            assert_eq!((mesh.get_vertex(index).length() - 2.0).abs() < 1e-12, true);
+        }
+        let volume = mesh.analyze_topology().signed_volume;
+        assert_solid(&mesh, volume);
+        assert_eq!(volume < 32.0 / 3.0 * PI && volume > 32.0, true);
+    }
+
+    #[test]
+    fn test_torus() {
+        let mesh = torus(0, 3.0, 1.0, 32, 16);
+        assert_eq!(mesh.triangles_count(), 32 * 16 * 2);
+        let volume = mesh.analyze_topology().signed_volume;
+        assert_solid(&mesh, volume);
+        // The exact torus has the volume of 2 * pi^2 * R * r^2
+        assert_eq!((volume - 6.0 * PI * PI).abs() < 0.05 * volume, true);
+    }
+
+    #[test]
+    fn test_wedge() {
+        let mesh = wedge(0, 2.0, 3.0, 4.0);
+        assert_eq!(mesh.triangles_count(), 8);
+        assert_solid(&mesh, 12.0);
+    }
+
+    #[test]
+    fn test_pyramid() {
+        let mesh = pyramid(0, 3.0, 3.0, 4.0);
+        assert_eq!(mesh.triangles_count(), 6);
+        assert_solid(&mesh, 12.0);
+    }
+
+    #[test]
+    fn test_tube() {
+        let mesh = tube(0, 2.0, 1.0, 3.0, 20);
+        assert_eq!(mesh.triangles_count(), 160);
+        assert_solid(&mesh, (get_polygon_area(2.0, 20) - get_polygon_area(1.0, 20)) * 3.0);
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let invalid = [
+            cuboid(4, 1.0, -2.0, 3.0),
+            cylinder(4, 1.0, -1.0, 8),
+            cylinder(4, 1.0, 1.0, 2),
+            cone(4, 0.0, 1.0, 8),
+            uv_sphere(4, -1.0, 8, 4),
+            uv_sphere(4, 1.0, 8, 1),
+            icosphere(4, f64::INFINITY, 1),
+            torus(4, 1.0, 1.0, 8, 8),
+            torus(4, 3.0, 1.0, 8, 2),
+            wedge(4, 1.0, 2.0, 0.0),
+            pyramid(4, 1.0, 2.0, f64::NAN),
+            tube(4, 1.0, 2.0, 1.0, 8),
+            tube(4, 1.0, 0.0, 1.0, 8),
+        ];
+        for mesh in invalid {
+            assert_eq!(mesh, Mesh::new(4, vec![], vec![]));
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use std::cell::Cell;
+use crate::file::File;
+use crate::spatial::{BoundingBox, Bvh, Triangle};
+use crate::vector::Vector;
+
+/// Represents an intersection of a ray with a triangle of an element.
+#[derive(Clone, Debug)]
+pub struct Hit {
+    /// The globally unique identifier of the element that was hit.
+    pub guid: String,
+    /// The index of the hit triangle in the mesh of the element.
+    pub triangle_index: usize,
+    /// The distance from the ray origin to the hit point.
+    pub distance: f64,
+    /// The hit point in the world space.
+    pub point: Vector,
+    /// The unit normal of the hit triangle in the world space, following its winding order.
+    pub normal: Vector,
+}
+
+impl PartialEq for Hit {
+    fn eq(&self, other: &Self) -> bool {
+        self.guid == other.guid && self.triangle_index == other.triangle_index && self.distance == other.distance
+            && self.point == other.point && self.normal == other.normal
+    }
+}
+
+/// Returns the distance along the unit direction at which the ray hits the triangle.
+/// Both sides of the triangle are hit, hits behind the origin are ignored.
+fn intersect_triangle(origin: &Vector, direction: &Vector, vertices: &[Vector; 3]) -> Option<f64> {
+    let edge1 = vertices[1] - vertices[0];
+    let edge2 = vertices[2] - vertices[0];
+    let p = direction.cross(&edge2);
+    let determinant = edge1.dot(&p);
+    if determinant.abs() < 1e-12 * edge1.length() * edge2.length() || determinant == 0.0 {
+        return None;
+    }
+    let inverse = 1.0 / determinant;
+    let s = *origin - vertices[0];
+    let u = s.dot(&p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(&edge1);
+    let v = direction.dot(&q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = edge2.dot(&q) * inverse;
+    if distance < 0.0 {
+        return None;
+    }
+    Some(distance)
+}
+
+/// Returns the distance along the direction at which the ray enters the box, None if it misses it.
+fn intersect_box(origin: &Vector, inverse_direction: &Vector, bounding_box: &BoundingBox) -> Option<f64> {
+    let mut near = 0.0_f64;
+    let mut far = f64::INFINITY;
+    for axis in 0..3 {
+        let t1 = (bounding_box.min.get(axis) - origin.get(axis)) * inverse_direction.get(axis);
+        let t2 = (bounding_box.max.get(axis) - origin.get(axis)) * inverse_direction.get(axis);
+        // NaN appears when the ray lies exactly in a slab plane, min/max skip it then.
+        near = near.max(t1.min(t2));
+        far = far.min(t1.max(t2));
+    }
+    if near <= far { Some(near) } else { None }
+}
+
+/// Skewed ray directions used to test if a point is inside a closed surface.
+/// They avoid the axes and diagonals, along which edges and vertices of typical models lie.
+const INSIDE_TEST_DIRECTIONS: [Vector; 3] = [
+    Vector { x: 1.0, y: 0.3127, z: 0.1871 },
+    Vector { x: -0.2213, y: 1.0, z: 0.4109 },
+    Vector { x: 0.1597, y: -0.3719, z: 1.0 },
+];
+
+impl Bvh {
+    fn create_hit(&self, triangle: &Triangle, origin: &Vector, direction: &Vector, distance: f64) -> Hit {
+        let [a, b, c] = triangle.vertices;
+        Hit {
+            guid: self.get_guid(triangle.element_index).to_string(),
+            triangle_index: triangle.triangle_index,
+            distance,
+            point: *origin + *direction * distance,
+            normal: (b - a).cross(&(c - a)).normalize(),
+        }
+    }
+
+    /// Returns true if the point is inside the closed surface of the element at the given position,
+    /// by the parity of crossings of rays in a few directions, taking the majority when they disagree.
+    /// Returns None if the point lies on the surface, closer to it than the tolerance.
+    pub(crate) fn contains_point(&self, point: &Vector, element_index: usize, tolerance: f64) -> Option<bool> {
+        let mut inside_votes = 0;
+        for direction in &INSIDE_TEST_DIRECTIONS {
+            let direction = direction.normalize();
+            let inverse_direction = Vector::new(1.0 / direction.x, 1.0 / direction.y, 1.0 / direction.z);
+            let mut crossings = 0;
+            let mut on_surface = false;
+            self.visit_filtered(
+                |bounds| intersect_box(point, &inverse_direction, bounds).is_some(),
+                |triangle| {
+                    if triangle.element_index != element_index {
+                        return;
+                    }
+                    if let Some(distance) = intersect_triangle(point, &direction, &triangle.vertices) {
+                        if distance <= tolerance {
+                            on_surface = true;
+                        } else {
+                            crossings += 1;
+                        }
+                    }
+                });
+            if on_surface {
+                return None;
+            }
+            if crossings % 2 == 1 {
+                inside_votes += 1;
+            }
+        }
+        Some(inside_votes * 2 > INSIDE_TEST_DIRECTIONS.len())
+    }
+
+    /// Returns the closest intersection of the ray with any element.
+    /// The direction doesn't have to be normalized, None is returned for the zero direction.
+    pub fn raycast(&self, origin: &Vector, direction: &Vector) -> Option<Hit> {
+        let direction = direction.normalize();
+        if direction.length() == 0.0 {
+            return None;
+        }
+        let inverse_direction = Vector::new(1.0 / direction.x, 1.0 / direction.y, 1.0 / direction.z);
+        let closest_distance = Cell::new(f64::INFINITY);
+        let mut closest_triangle: Option<Triangle> = None;
+        self.visit_filtered(
+            |bounds| match intersect_box(origin, &inverse_direction, bounds) {
+                Some(distance) => distance <= closest_distance.get(),
+                None => false,
+            },
+            |triangle| {
+                if let Some(distance) = intersect_triangle(origin, &direction, &triangle.vertices) {
+                    if distance < closest_distance.get() {
+                        closest_distance.set(distance);
+                        closest_triangle = Some(*triangle);
+                    }
+                }
+            });
+        closest_triangle.map(|triangle| self.create_hit(&triangle, origin, &direction, closest_distance.get()))
+    }
+
+    /// Returns all intersections of the ray with elements, sorted from the closest one.
+    /// The direction doesn't have to be normalized, no hits are returned for the zero direction.
+    pub fn raycast_all(&self, origin: &Vector, direction: &Vector) -> Vec<Hit> {
+        let direction = direction.normalize();
+        if direction.length() == 0.0 {
+            return Vec::new();
+        }
+        let inverse_direction = Vector::new(1.0 / direction.x, 1.0 / direction.y, 1.0 / direction.z);
+        let mut hits = Vec::new();
+        self.visit_filtered(
+            |bounds| intersect_box(origin, &inverse_direction, bounds).is_some(),
+            |triangle| {
+                if let Some(distance) = intersect_triangle(origin, &direction, &triangle.vertices) {
+                    hits.push(self.create_hit(triangle, origin, &direction, distance));
+                }
+            });
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance)
+            .then(a.guid.cmp(&b.guid))
+            .then(a.triangle_index.cmp(&b.triangle_index)));
+        hits
+    }
+}
+
+impl File {
+    /// Returns the closest intersection of the ray with any element of the file.
+    /// It builds a new Bvh on every call, build one with Bvh::new to cast many rays.
+    pub fn raycast(&self, origin: Vector, direction: Vector) -> Option<Hit> { Bvh::new(self).raycast(&origin, &direction) }
+
+    /// Returns all intersections of the ray with elements of the file, sorted from the closest one.
+    /// It builds a new Bvh on every call, build one with Bvh::new to cast many rays.
+    pub fn raycast_all(&self, origin: Vector, direction: Vector) -> Vec<Hit> { Bvh::new(self).raycast_all(&origin, &direction) }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::color::Color;
+    use crate::element::Element;
+    use crate::mesh::Mesh;
+    use crate::rotation::Rotation;
+    use super::*;
+
+    fn get_file_with_two_plates() -> File {
+        let mesh = Mesh::new(
+            0,
+            vec![
+                0.0, 0.0, 0.0,
+                10.0, 0.0, 0.0,
+                10.0, 10.0, 0.0,
+                0.0, 10.0, 0.0
+            ],
+            vec![
+                0, 1, 2,
+                0, 2, 3
+            ]
+        );
+
+        let lower = Element::new(
+            0,
+            Vector::new(0., 0., 0.),
+            Rotation::new(0., 0., 0., 1.),
+            String::from("c1b5e2b4-6f4b-4f4b-8a53-8e1f0e6f1a01"),
+            String::from("Slab"),
+            Color::new(120, 120, 120, 255),
+            None,
+            HashMap::new(),
+        );
+
+        let upper = Element::new(
+            0,
+            Vector::new(0., 0., 5.),
+            Rotation::new(0., 0., 0., 1.),
+            String::from("c1b5e2b4-6f4b-4f4b-8a53-8e1f0e6f1a02"),
+            String::from("Slab"),
+            Color::new(120, 120, 120, 255),
+            None,
+            HashMap::new(),
+        );
+
+        File::new(String::from("1.0.0"), vec![mesh], vec![lower, upper], HashMap::new())
+    }
+
+    #[test]
+    fn test_raycast_closest() {
+        let file = get_file_with_two_plates();
+        let actual = file.raycast(Vector::new(2.0, 7.0, 20.0), Vector::new(0.0, 0.0, -3.0));
+        assert_eq!(actual.is_some(), true);
+        let hit = actual.unwrap();
+        assert_eq!(hit.guid, "c1b5e2b4-6f4b-4f4b-8a53-8e1f0e6f1a02");
+        assert_eq!(hit.triangle_index, 1);
+        assert_eq!(hit.distance, 15.0);
+        assert_eq!(hit.point.eq(&Vector::new(2.0, 7.0, 5.0)), true);
+        assert_eq!(hit.normal.eq(&Vector::new(0.0, 0.0, 1.0)), true);
+    }
+
+    #[test]
+    fn test_raycast_from_below() {
+        let file = get_file_with_two_plates();
+        let hit = file.raycast(Vector::new(7.0, 2.0, -1.0), Vector::new(0.0, 0.0, 1.0)).unwrap();
+        assert_eq!(hit.guid, "c1b5e2b4-6f4b-4f4b-8a53-8e1f0e6f1a01");
+        assert_eq!(hit.triangle_index, 0);
+        assert_eq!(hit.distance, 1.0);
+    }
+
+    #[test]
+    fn test_raycast_miss() {
+        let file = get_file_with_two_plates();
+        assert_eq!(file.raycast(Vector::new(20.0, 7.0, 20.0), Vector::new(0.0, 0.0, -1.0)).is_none(), true);
+        assert_eq!(file.raycast(Vector::new(2.0, 7.0, 20.0), Vector::new(0.0, 0.0, 1.0)).is_none(), true);
+        assert_eq!(file.raycast(Vector::new(2.0, 7.0, 20.0), Vector::new(0.0, 0.0, 0.0)).is_none(), true);
+    }
+
+    #[test]
+    fn test_raycast_all() {
+        let file = get_file_with_two_plates();
+        let actual = file.raycast_all(Vector::new(2.0, 7.0, 20.0), Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(actual.len(), 2);
+        assert_eq!(actual[0].guid, "c1b5e2b4-6f4b-4f4b-8a53-8e1f0e6f1a02");
+        assert_eq!(actual[0].distance, 15.0);
+        assert_eq!(actual[1].guid, "c1b5e2b4-6f4b-4f4b-8a53-8e1f0e6f1a01");
+        assert_eq!(actual[1].distance, 20.0);
+    }
+
+    #[test]
+    fn test_raycast_rotated_element() {
+        let mut file = get_file_with_two_plates();
+        // Rotate the upper plate by 90 degrees around x axis, so it stands vertically
+        let half = std::f64::consts::FRAC_PI_4;
+        file.elements[1].rotation = Rotation::new(half.sin(), 0.0, 0.0, half.cos());
+        let hit = file.raycast(Vector::new(3.0, -10.0, 8.0), Vector::new(0.0, 1.0, 0.0)).unwrap();
+        assert_eq!(hit.guid, "c1b5e2b4-6f4b-4f4b-8a53-8e1f0e6f1a02");
+        assert_eq!((hit.distance - 10.0).abs() < 1e-9, true);
+        assert_eq!((hit.normal.y + 1.0).abs() < 1e-9, true);
+    }
+
+    #[test]
+    fn test_raycast_reused_bvh_matches_file() {
+        let file = get_file_with_two_plates();
+        let bvh = Bvh::new(&file);
+        let origin = Vector::new(4.0, 4.0, 20.0);
+        let direction = Vector::new(0.1, 0.2, -1.0);
+        assert_eq!(bvh.raycast(&origin, &direction).eq(&file.raycast(origin, direction)), true);
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let element = Element::new(0, Vector::new(0.0, 0.0, 0.0), Rotation::new(0., 0., 0., 1.), String::from("cube"),
+                                   String::from("Other"), Color::new(255, 255, 255, 255), None, HashMap::new());
+        let file = File::new(String::from("1.0.0"), vec![crate::primitives::cuboid(0, 2.0, 2.0, 2.0)], vec![element], HashMap::new());
+        let bvh = Bvh::new(&file);
+        assert_eq!(bvh.contains_point(&Vector::new(0.0, 0.0, 1.0), 0, 1e-9), Some(true));
+        assert_eq!(bvh.contains_point(&Vector::new(0.5, -0.9, 0.1), 0, 1e-9), Some(true));
+        assert_eq!(bvh.contains_point(&Vector::new(0.0, 0.0, 3.0), 0, 1e-9), Some(false));
+        assert_eq!(bvh.contains_point(&Vector::new(-5.0, 0.0, 1.0), 0, 1e-9), Some(false));
+        assert_eq!(bvh.contains_point(&Vector::new(1.0, 1.0, 2.0), 0, 1e-9), None);
+        assert_eq!(bvh.contains_point(&Vector::new(0.3, 0.2, 0.0), 0, 1e-9), None);
+    }
+}
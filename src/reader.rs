@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{BufReader, Read};
+use std::marker::PhantomData;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::Deserializer;
+use crate::element::Element;
+use crate::mesh::Mesh;
+
+/// Represents a mesh or an element read from a file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Item {
+    /// The mesh read from the meshes list.
+    Mesh(Mesh),
+    /// The element read from the elements list.
+    Element(Element),
+}
+
+/// Represents the parts of a file other than meshes and elements, known once the whole file is read.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Header {
+    /// The schema version of the file.
+    pub schema_version: String,
+    /// Additional information about the file.
+    pub info: HashMap<String, String>,
+}
+
+/// Deserializes a list, passing every value to the callback instead of collecting them.
+struct ItemsSeed<'a, T> {
+    on_value: &'a mut dyn FnMut(T),
+    marker: PhantomData<T>,
+}
+
+impl<'de, T: DeserializeOwned> DeserializeSeed<'de> for ItemsSeed<'_, T> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> { deserializer.deserialize_seq(self) }
+}
+
+impl<'de, T: DeserializeOwned> Visitor<'de> for ItemsSeed<'_, T> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result { formatter.write_str("a list") }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(value) = seq.next_element::<T>()? {
+            (self.on_value)(value);
+        }
+        Ok(())
+    }
+}
+
+/// Deserializes the top level object of a file, with keys in any order.
+struct FileVisitor<'a, F> {
+    on_item: &'a mut F,
+}
+
+impl<'de, F: FnMut(Item)> Visitor<'de> for FileVisitor<'_, F> {
+    type Value = Header;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result { formatter.write_str("a dotbim file") }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Header, A::Error> {
+        let on_item = self.on_item;
+        let mut schema_version: Option<String> = None;
+        let mut info: Option<HashMap<String, String>> = None;
+        let (mut has_meshes, mut has_elements) = (false, false);
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "schema_version" if schema_version.is_some() => return Err(de::Error::duplicate_field("schema_version")),
+                "schema_version" => schema_version = Some(map.next_value()?),
+                "info" if info.is_some() => return Err(de::Error::duplicate_field("info")),
+                "info" => info = Some(map.next_value()?),
+                "meshes" if has_meshes => return Err(de::Error::duplicate_field("meshes")),
+                "meshes" => {
+                    has_meshes = true;
+                    map.next_value_seed(ItemsSeed { on_value: &mut |mesh| on_item(Item::Mesh(mesh)), marker: PhantomData })?;
+                }
+                "elements" if has_elements => return Err(de::Error::duplicate_field("elements")),
+                "elements" => {
+                    has_elements = true;
+                    map.next_value_seed(ItemsSeed { on_value: &mut |element| on_item(Item::Element(element)), marker: PhantomData })?;
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        if !has_meshes {
+            return Err(de::Error::missing_field("meshes"));
+        }
+        if !has_elements {
+            return Err(de::Error::missing_field("elements"));
+        }
+        let schema_version = schema_version.ok_or_else(|| de::Error::missing_field("schema_version"))?;
+        let info = info.ok_or_else(|| de::Error::missing_field("info"))?;
+        Ok(Header { schema_version, info })
+    }
+}
+
+/// Reads the file from the reader, passing meshes and elements to the callback one at a time as they are parsed,
+/// so only one of them is held in memory at once. Returns the header once the whole file is read.
+/// Top level keys can come in any order, unknown keys are skipped.
+pub fn read_stream<R: Read, F: FnMut(Item)>(reader: R, mut on_item: F) -> Result<Header, serde_json::Error> {
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+    let header = deserializer.deserialize_map(FileVisitor { on_item: &mut on_item })?;
+    deserializer.end()?;
+    Ok(header)
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use serde_json::to_string;
+    use crate::builder::{ElementBuilder, FileBuilder};
+    use crate::file::File;
+    use crate::primitives::{cuboid, icosphere};
+    use super::*;
+
+    fn get_file() -> File {
+        let mut builder = FileBuilder::new();
+        builder.info("Author", "Jane Doe");
+        let box_id = builder.add_mesh(cuboid(0, 1.0, 2.0, 3.0));
+        let sphere_id = builder.add_mesh(icosphere(0, 1.0, 1));
+        for i in 0..5 {
+            builder.add_element(ElementBuilder::new(if i % 2 == 0 { box_id } else { sphere_id }).guid(&i.to_string()).build());
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn test_read_stream() {
+        let file = get_file();
+        let json = to_string(&file).unwrap();
+        let mut meshes = Vec::new();
+        let mut elements = Vec::new();
+        let header = read_stream(json.as_bytes(), |item| match item {
+            Item::Mesh(mesh) => meshes.push(mesh),
+            Item::Element(element) => elements.push(element),
+        }).unwrap();
+        assert_eq!(header.schema_version, "1.0.0");
+        assert_eq!(header.info, file.info);
+        assert_eq!(meshes, file.meshes);
+        assert_eq!(elements, file.elements);
+    }
+
+    #[test]
+    fn test_read_stream_statistics() {
+        let json = to_string(&get_file()).unwrap();
+        let mut triangles = 0;
+        let mut elements = 0;
+        read_stream(json.as_bytes(), |item| match item {
+            Item::Mesh(mesh) => triangles += mesh.triangles_count(),
+            Item::Element(_) => elements += 1,
+        }).unwrap();
+        assert_eq!(triangles, 12 + 80);
+        assert_eq!(elements, 5);
+    }
+
+    #[test]
+    fn test_read_stream_any_key_order() {
+        let json = "{\"info\":{\"A\":\"B\"},\"elements\":[{\"mesh_id\":0,\"vector\":{\"x\":0.0,\"y\":0.0,\"z\":0.0},\
+                    \"rotation\":{\"qx\":0.0,\"qy\":0.0,\"qz\":0.0,\"qw\":1.0},\"guid\":\"g\",\"type\":\"Plate\",\
+                    \"color\":{\"r\":1,\"g\":2,\"b\":3,\"a\":4},\"info\":{}}],\"extra\":[1,{\"x\":null}],\
+                    \"meshes\":[{\"mesh_id\":0,\"coordinates\":[0.0,0.0,0.0],\"indices\":[]}],\"schema_version\":\"1.0.0\"}";
+        let mut items = Vec::new();
+        let header = read_stream(json.as_bytes(), |item| items.push(item)).unwrap();
+        assert_eq!(header.info.get("A").map(String::as_str), Some("B"));
+        assert_eq!(matches!(items[0], Item::Element(_)), true);
+        assert_eq!(matches!(items[1], Item::Mesh(_)), true);
+    }
+
+    #[test]
+    fn test_read_stream_errors() {
+        let missing = "{\"schema_version\":\"1.0.0\",\"meshes\":[],\"elements\":[]}";
+        let error = read_stream(missing.as_bytes(), |_| {}).unwrap_err();
+        assert_eq!(error.to_string().contains("missing field `info`"), true);
+
+        let duplicate = "{\"schema_version\":\"1.0.0\",\"meshes\":[],\"meshes\":[],\"elements\":[],\"info\":{}}";
+        assert_eq!(read_stream(duplicate.as_bytes(), |_| {}).is_err(), true);
+
+        let trailing = "{\"schema_version\":\"1.0.0\",\"meshes\":[],\"elements\":[],\"info\":{}} {}";
+        assert_eq!(read_stream(trailing.as_bytes(), |_| {}).is_err(), true);
+
+        let truncated = to_string(&get_file()).unwrap();
+        let mut count = 0;
+        assert_eq!(read_stream(&truncated.as_bytes()[..truncated.len() - 40], |_| count += 1).is_err(), true);
+        assert_eq!(count, 6);
+    }
+}
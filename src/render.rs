@@ -0,0 +1,545 @@
+use crate::color::Color;
+use crate::file::File;
+use crate::spatial::{get_world_triangles, BoundingBox};
+use crate::vector::Vector;
+
+/// Distance of the near clipping plane for the perspective projection, relative to the scene size.
+const NEAR_PLANE_FACTOR: f64 = 1e-4;
+
+/// Represents the way the camera projects the scene onto the image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    /// Perspective projection with the given vertical field of view in degrees.
+    Perspective { vertical_fov: f64 },
+    /// Orthographic projection showing the given height of the scene in world units.
+    Orthographic { height: f64 },
+}
+
+/// Represents the corner of the model from which an isometric view looks at it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IsometricPreset {
+    /// View from the corner with the smallest y and the largest x.
+    SouthEast,
+    /// View from the corner with the smallest x and y.
+    SouthWest,
+    /// View from the corner with the largest x and y.
+    NorthEast,
+    /// View from the corner with the smallest x and the largest y.
+    NorthWest,
+}
+
+impl IsometricPreset {
+    /// Returns the direction in which the camera looks for this preset.
+    pub fn get_direction(&self) -> Vector {
+        match self {
+            IsometricPreset::SouthEast => Vector::new(-1.0, 1.0, -1.0),
+            IsometricPreset::SouthWest => Vector::new(1.0, 1.0, -1.0),
+            IsometricPreset::NorthEast => Vector::new(-1.0, -1.0, -1.0),
+            IsometricPreset::NorthWest => Vector::new(1.0, -1.0, -1.0),
+        }
+    }
+}
+
+/// Represents a camera looking at the scene.
+#[derive(Clone, Copy, Debug)]
+pub struct Camera {
+    /// The position of the camera.
+    pub position: Vector,
+    /// The point the camera looks at.
+    pub target: Vector,
+    /// The direction pointing up the image.
+    pub up: Vector,
+    /// The projection of the camera.
+    pub projection: Projection,
+}
+
+impl Camera {
+    /// Returns a new Camera
+    pub fn new(position: Vector, target: Vector, up: Vector, projection: Projection) -> Camera {
+        Camera { position, target, up, projection }
+    }
+
+    /// Returns a Camera looking along the direction, placed so the whole bounding box is visible
+    /// in an image with the given aspect ratio (width divided by height).
+    /// For the orthographic projection its height is replaced with the fitted one.
+    pub fn fit_to_bounds(bounds: &BoundingBox, direction: &Vector, projection: Projection, aspect: f64) -> Camera {
+        let direction = if direction.length() == 0.0 { Vector::new(0.0, 0.0, -1.0) } else { direction.normalize() };
+        let up = if direction.cross(&Vector::new(0.0, 0.0, 1.0)).length() < 1e-9 {
+            Vector::new(0.0, 1.0, 0.0)
+        } else {
+            Vector::new(0.0, 0.0, 1.0)
+        };
+        let (center, radius) = if bounds.is_empty() {
+            (Vector::new(0.0, 0.0, 0.0), 1.0)
+        } else {
+            (bounds.center(), (bounds.size().length() * 0.5).max(1e-9))
+        };
+        // The sphere around the box has to fit both vertically and horizontally.
+        let aspect = if aspect > 0.0 { aspect } else { 1.0 };
+        let vertical_extent = radius * (1.0 / aspect).max(1.0);
+        match projection {
+            Projection::Perspective { vertical_fov } => {
+                let half_fov = (vertical_fov.to_radians() * 0.5).clamp(1e-3, std::f64::consts::FRAC_PI_2 - 1e-3);
+                let horizontal_half_fov = (half_fov.tan() * aspect).atan();
+                let distance = radius / half_fov.min(horizontal_half_fov).sin();
+                Camera::new(center - direction * distance, center, up, projection)
+            }
+            Projection::Orthographic { .. } => {
+                Camera::new(center - direction * (radius * 2.0), center, up, Projection::Orthographic { height: vertical_extent * 2.0 })
+            }
+        }
+    }
+
+    /// Returns a Camera looking at the bounding box from one of its corners, like in isometric drawings.
+    pub fn isometric(bounds: &BoundingBox, preset: IsometricPreset, projection: Projection, aspect: f64) -> Camera {
+        Camera::fit_to_bounds(bounds, &preset.get_direction(), projection, aspect)
+    }
+
+    /// Returns right, up and forward unit vectors of the camera.
+    fn get_basis(&self) -> (Vector, Vector, Vector) {
+        let forward = (self.target - self.position).normalize();
+        let mut right = forward.cross(&self.up).normalize();
+        if right.length() == 0.0 {
+            right = forward.cross(&Vector::new(1.0, 0.0, 0.0)).normalize();
+        }
+        let up = right.cross(&forward);
+        (right, up, forward)
+    }
+}
+
+/// Represents the way faces are shaded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shading {
+    /// Faces are filled with their colors, without any lighting.
+    Flat,
+    /// Faces are lit by a directional light using their normals, both sides of a face are lit.
+    Lambert,
+}
+
+/// Represents options of rendering.
+#[derive(Clone, Copy, Debug)]
+pub struct RenderOptions {
+    /// The width of the image in pixels.
+    pub width: u32,
+    /// The height of the image in pixels.
+    pub height: u32,
+    /// The color of pixels not covered by any element.
+    pub background: Color,
+    /// The way faces are shaded.
+    pub shading: Shading,
+    /// The direction in which the light shines, used by Lambert shading.
+    pub light_direction: Vector,
+    /// The fraction of the light that reaches faces not facing the light, used by Lambert shading.
+    pub ambient: f64,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            width: 256,
+            height: 256,
+            background: Color::new(255, 255, 255, 255),
+            shading: Shading::Lambert,
+            light_direction: Vector::new(-0.3, 0.5, -1.0),
+            ambient: 0.35,
+        }
+    }
+}
+
+/// Represents an image made of RGBA pixels, 8 bits per channel, stored row by row from the top.
+#[derive(Clone, Debug)]
+pub struct Image {
+    /// The width of the image in pixels.
+    pub width: u32,
+    /// The height of the image in pixels.
+    pub height: u32,
+    /// The pixels as [r, g, b, a] bytes.
+    pub pixels: Vec<u8>,
+}
+
+impl PartialEq for Image {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width && self.height == other.height && self.pixels == other.pixels
+    }
+}
+
+impl Image {
+    /// Returns a new Image filled with the given color
+    pub fn new(width: u32, height: u32, color: &Color) -> Image {
+        let pixel = [to_byte(color.r), to_byte(color.g), to_byte(color.b), to_byte(color.a)];
+        Image { width, height, pixels: pixel.repeat(width as usize * height as usize) }
+    }
+
+    /// Returns the color of the pixel, (0, 0) being the top left corner.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Color {
+        let start = (y as usize * self.width as usize + x as usize) * 4;
+        Color::new(self.pixels[start] as i32, self.pixels[start + 1] as i32, self.pixels[start + 2] as i32, self.pixels[start + 3] as i32)
+    }
+
+    /// Writes the image encoded as PNG. Needs the png feature.
+    #[cfg(feature = "png")]
+    pub fn write_png<W: std::io::Write>(&self, writer: W) -> Result<(), png::EncodingError> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut png_writer = encoder.write_header()?;
+        png_writer.write_image_data(&self.pixels)?;
+        png_writer.finish()
+    }
+
+    /// Saves the image as PNG file under the given path. Needs the png feature.
+    #[cfg(feature = "png")]
+    pub fn save_png<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), png::EncodingError> {
+        let file = std::fs::File::create(path)?;
+        self.write_png(std::io::BufWriter::new(file))
+    }
+
+    /// Blends the color over the pixel at the given position in the pixels list.
+    fn blend(&mut self, index: usize, color: &[f64; 4]) {
+        let alpha = color[3];
+        let destination_alpha = self.pixels[index * 4 + 3] as f64 / 255.0;
+        let result_alpha = alpha + destination_alpha * (1.0 - alpha);
+        for (channel, source) in color.iter().take(3).enumerate() {
+            let destination = self.pixels[index * 4 + channel] as f64;
+            let value = if result_alpha > 0.0 {
+                (source * alpha + destination * destination_alpha * (1.0 - alpha)) / result_alpha
+            } else {
+                0.0
+            };
+            self.pixels[index * 4 + channel] = value.round().clamp(0.0, 255.0) as u8;
+        }
+        self.pixels[index * 4 + 3] = (result_alpha * 255.0).round().clamp(0.0, 255.0) as u8;
+    }
+}
+
+/// Returns the color component clamped to the byte range.
+fn to_byte(value: i32) -> u8 { value.clamp(0, 255) as u8 }
+
+/// Vertex projected onto the image.
+#[derive(Clone, Copy, Debug)]
+struct ScreenVertex {
+    x: f64,
+    y: f64,
+    /// Value interpolated linearly across the image, smaller values are closer to the camera.
+    depth: f64,
+}
+
+/// Triangle prepared for rasterization.
+struct ScreenTriangle {
+    vertices: Vec<ScreenVertex>,
+    color: [f64; 4],
+    /// Depth of the triangle center in the camera space, used to sort transparent triangles.
+    distance: f64,
+}
+
+/// Returns the file rendered into an image with a z-buffer.
+///
+/// Opaque faces are drawn first, then transparent faces from the furthest one, blended over them.
+/// Colors come from face colors of elements if they have them, otherwise from colors of elements.
+pub fn render(file: &File, camera: &Camera, options: &RenderOptions) -> Image {
+    let mut image = Image::new(options.width, options.height, &options.background);
+    if options.width == 0 || options.height == 0 {
+        return image;
+    }
+    let (right, up, forward) = camera.get_basis();
+    let aspect = options.width as f64 / options.height as f64;
+    let light = options.light_direction.normalize();
+    let triangles = get_world_triangles(file);
+
+    let mut scene_bounds = BoundingBox::empty();
+    for triangle in &triangles {
+        scene_bounds = scene_bounds.union(&triangle.bounding_box());
+    }
+    let near = if scene_bounds.is_empty() { 1e-6 } else { (scene_bounds.size().length() * NEAR_PLANE_FACTOR).max(1e-9) };
+
+    let mut opaque = Vec::new();
+    let mut transparent = Vec::new();
+    for triangle in &triangles {
+        let element = &file.elements[triangle.element_index];
+        let color = element.triangle_color(triangle.triangle_index);
+        if color.a <= 0 {
+            continue;
+        }
+
+        let [a, b, c] = triangle.vertices;
+        let intensity = match options.shading {
+            Shading::Flat => 1.0,
+            Shading::Lambert => {
+                let normal = (b - a).cross(&(c - a)).normalize();
+                options.ambient + (1.0 - options.ambient) * normal.dot(&light).abs()
+            }
+        };
+        let shaded = [
+            to_byte(color.r) as f64 * intensity,
+            to_byte(color.g) as f64 * intensity,
+            to_byte(color.b) as f64 * intensity,
+            to_byte(color.a) as f64 / 255.0,
+        ];
+
+        let camera_space: Vec<Vector> = triangle.vertices.iter().map(|vertex| {
+            let relative = *vertex - camera.position;
+            Vector::new(relative.dot(&right), relative.dot(&up), relative.dot(&forward))
+        }).collect();
+        let clipped = match camera.projection {
+            Projection::Perspective { .. } => clip_near(&camera_space, near),
+            Projection::Orthographic { .. } => camera_space,
+        };
+        if clipped.len() < 3 {
+            continue;
+        }
+        let distance = clipped.iter().map(|vertex| vertex.z).sum::<f64>() / clipped.len() as f64;
+        let vertices = clipped.iter().map(|vertex| project(vertex, &camera.projection, aspect, options)).collect();
+        let screen_triangle = ScreenTriangle { vertices, color: shaded, distance };
+        if color.a >= 255 {
+            opaque.push(screen_triangle);
+        } else {
+            transparent.push(screen_triangle);
+        }
+    }
+    transparent.sort_by(|a, b| b.distance.total_cmp(&a.distance));
+
+    let mut depth_buffer = vec![f64::INFINITY; options.width as usize * options.height as usize];
+    for triangle in &opaque {
+        rasterize(&mut image, &mut depth_buffer, triangle, true);
+    }
+    for triangle in &transparent {
+        rasterize(&mut image, &mut depth_buffer, triangle, false);
+    }
+    image
+}
+
+/// Returns the polygon clipped to the part lying in front of the near plane.
+fn clip_near(polygon: &[Vector], near: f64) -> Vec<Vector> {
+    let mut result = Vec::with_capacity(4);
+    for i in 0..polygon.len() {
+        let current = polygon[i];
+        let next = polygon[(i + 1) % polygon.len()];
+        if current.z >= near {
+            result.push(current);
+        }
+        if (current.z < near) != (next.z < near) {
+            let t = (near - current.z) / (next.z - current.z);
+            result.push(current + (next - current) * t);
+        }
+    }
+    result
+}
+
+/// Returns the camera-space point projected onto the image.
+fn project(point: &Vector, projection: &Projection, aspect: f64, options: &RenderOptions) -> ScreenVertex {
+    let (x, y, depth) = match projection {
+        Projection::Perspective { vertical_fov } => {
+            let tangent = (vertical_fov.to_radians() * 0.5).tan();
+            (point.x / (point.z * tangent * aspect), point.y / (point.z * tangent), -1.0 / point.z)
+        }
+        Projection::Orthographic { height } => {
+            let half = height * 0.5;
+            (point.x / (half * aspect), point.y / half, point.z)
+        }
+    };
+    ScreenVertex {
+        x: (x + 1.0) * 0.5 * options.width as f64,
+        y: (1.0 - y) * 0.5 * options.height as f64,
+        depth,
+    }
+}
+
+/// Draws the convex polygon into the image, testing and optionally writing the depth buffer.
+fn rasterize(image: &mut Image, depth_buffer: &mut [f64], triangle: &ScreenTriangle, write_depth: bool) {
+    let width = image.width as usize;
+    let height = image.height as usize;
+    let first = triangle.vertices[0];
+    for i in 1..triangle.vertices.len() - 1 {
+        let (mut second, mut third) = (triangle.vertices[i], triangle.vertices[i + 1]);
+        let mut area = edge_function(&first, &second, &third);
+        if area == 0.0 || !area.is_finite() {
+            continue;
+        }
+        if area < 0.0 {
+            std::mem::swap(&mut second, &mut third);
+            area = -area;
+        }
+        let min_x = first.x.min(second.x).min(third.x).floor().max(0.0) as usize;
+        let max_x = (first.x.max(second.x).max(third.x).ceil().min(width as f64).max(0.0)) as usize;
+        let min_y = first.y.min(second.y).min(third.y).floor().max(0.0) as usize;
+        let max_y = (first.y.max(second.y).max(third.y).ceil().min(height as f64).max(0.0)) as usize;
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let pixel = ScreenVertex { x: x as f64 + 0.5, y: y as f64 + 0.5, depth: 0.0 };
+                let e0 = edge_function(&second, &third, &pixel);
+                let e1 = edge_function(&third, &first, &pixel);
+                let e2 = edge_function(&first, &second, &pixel);
+                if !is_covered(e0, &second, &third) || !is_covered(e1, &third, &first) || !is_covered(e2, &first, &second) {
+                    continue;
+                }
+                let depth = (e0 * first.depth + e1 * second.depth + e2 * third.depth) / area;
+                let index = y * width + x;
+                if depth >= depth_buffer[index] {
+                    continue;
+                }
+                if write_depth {
+                    depth_buffer[index] = depth;
+                }
+                image.blend(index, &triangle.color);
+            }
+        }
+    }
+}
+
+/// Returns the doubled signed area of the triangle (a, b, point).
+fn edge_function(a: &ScreenVertex, b: &ScreenVertex, point: &ScreenVertex) -> f64 {
+    (b.x - a.x) * (point.y - a.y) - (b.y - a.y) * (point.x - a.x)
+}
+
+/// Returns true if the pixel with the given edge function value is covered by the triangle.
+/// Pixels lying exactly on an edge belong only to one of two triangles sharing it,
+/// so transparent faces are not blended twice along their diagonals.
+fn is_covered(edge_value: f64, a: &ScreenVertex, b: &ScreenVertex) -> bool {
+    if edge_value != 0.0 {
+        return edge_value > 0.0;
+    }
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    dy > 0.0 || (dy == 0.0 && dx < 0.0)
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::element::Element;
+    use crate::rotation::Rotation;
+    use crate::spatial::Bvh;
+    use crate::test_utils::get_cube;
+    use super::*;
+
+    fn get_cube_element(position: Vector, color: Color) -> Element {
+        Element::new(
+            0,
+            position,
+            Rotation::new(0., 0., 0., 1.),
+            String::from("9f61b565-06a2-4bef-8b72-f37091ab54d6"),
+            String::from("Brick"),
+            color,
+            None,
+            HashMap::new(),
+        )
+    }
+
+    fn get_file(elements: Vec<Element>) -> File {
+        File::new(String::from("1.0.0"), vec![get_cube(10.0)], elements, HashMap::new())
+    }
+
+    fn get_top_camera() -> Camera {
+        Camera::new(Vector::new(5.0, 5.0, 100.0), Vector::new(5.0, 5.0, 0.0), Vector::new(0.0, 1.0, 0.0),
+                    Projection::Orthographic { height: 20.0 })
+    }
+
+    fn get_flat_options() -> RenderOptions {
+        RenderOptions { width: 40, height: 40, shading: Shading::Flat, ..RenderOptions::default() }
+    }
+
+    #[test]
+    fn test_render_orthographic_flat() {
+        let file = get_file(vec![get_cube_element(Vector::new(0.0, 0.0, 0.0), Color::new(255, 0, 0, 255))]);
+        let image = render(&file, &get_top_camera(), &get_flat_options());
+        assert_eq!(image.width, 40);
+        assert_eq!(image.height, 40);
+        assert_eq!(image.pixels.len(), 40 * 40 * 4);
+        // The cube covers the middle half of the image
+        assert_eq!(image.get_pixel(20, 20).eq(&Color::new(255, 0, 0, 255)), true);
+        assert_eq!(image.get_pixel(11, 28).eq(&Color::new(255, 0, 0, 255)), true);
+        assert_eq!(image.get_pixel(9, 20).eq(&Color::new(255, 255, 255, 255)), true);
+        assert_eq!(image.get_pixel(0, 0).eq(&Color::new(255, 255, 255, 255)), true);
+    }
+
+    #[test]
+    fn test_render_depth_buffer() {
+        let file = get_file(vec![
+            get_cube_element(Vector::new(0.0, 0.0, 20.0), Color::new(0, 0, 255, 255)),
+            get_cube_element(Vector::new(0.0, 0.0, 0.0), Color::new(255, 0, 0, 255)),
+        ]);
+        let image = render(&file, &get_top_camera(), &get_flat_options());
+        assert_eq!(image.get_pixel(20, 20).eq(&Color::new(0, 0, 255, 255)), true);
+    }
+
+    #[test]
+    fn test_render_transparency() {
+        let file = get_file(vec![
+            get_cube_element(Vector::new(0.0, 0.0, 20.0), Color::new(0, 0, 255, 51)),
+            get_cube_element(Vector::new(0.0, 0.0, 0.0), Color::new(255, 0, 0, 255)),
+        ]);
+        let image = render(&file, &get_top_camera(), &get_flat_options());
+        let pixel = image.get_pixel(20, 20);
+        // Two faces of the transparent cube (top and bottom) cover the red one
+        assert_eq!(pixel.r, 163);
+        assert_eq!(pixel.g, 0);
+        assert_eq!(pixel.b, 92);
+        assert_eq!(pixel.a, 255);
+    }
+
+    #[test]
+    fn test_render_face_colors() {
+        let mut element = get_cube_element(Vector::new(0.0, 0.0, 0.0), Color::new(255, 0, 0, 255));
+        let mut face_colors = [[0, 0, 0, 255]; 12].concat();
+        // Top side is made of triangles 2 and 3
+        face_colors[8..16].copy_from_slice(&[0, 255, 0, 255, 0, 255, 0, 255]);
+        element.face_colors = Some(face_colors);
+        let image = render(&get_file(vec![element]), &get_top_camera(), &get_flat_options());
+        assert_eq!(image.get_pixel(20, 20).eq(&Color::new(0, 255, 0, 255)), true);
+    }
+
+    #[test]
+    fn test_render_lambert() {
+        let file = get_file(vec![get_cube_element(Vector::new(0.0, 0.0, 0.0), Color::new(200, 200, 200, 255))]);
+        let options = RenderOptions { light_direction: Vector::new(0.0, 0.0, -1.0), ambient: 0.5, ..get_flat_options() };
+        let lit = render(&file, &get_top_camera(), &RenderOptions { shading: Shading::Lambert, ..options });
+        assert_eq!(lit.get_pixel(20, 20).eq(&Color::new(200, 200, 200, 255)), true);
+        let side = RenderOptions { shading: Shading::Lambert, light_direction: Vector::new(1.0, 0.0, 0.0), ..options };
+        let dim = render(&file, &get_top_camera(), &side);
+        assert_eq!(dim.get_pixel(20, 20).eq(&Color::new(100, 100, 100, 255)), true);
+    }
+
+    #[test]
+    fn test_render_perspective_fit_and_isometric() {
+        let file = get_file(vec![get_cube_element(Vector::new(0.0, 0.0, 0.0), Color::new(255, 0, 0, 255))]);
+        let bounds = Bvh::new(&file).bounds();
+        let perspective = Camera::fit_to_bounds(&bounds, &Vector::new(0.0, 0.0, -1.0), Projection::Perspective { vertical_fov: 45.0 }, 1.0);
+        let image = render(&file, &perspective, &get_flat_options());
+        assert_eq!(image.get_pixel(20, 20).eq(&Color::new(255, 0, 0, 255)), true);
+        assert_eq!(image.get_pixel(0, 0).eq(&Color::new(255, 255, 255, 255)), true);
+
+        let isometric = Camera::isometric(&bounds, IsometricPreset::SouthEast, Projection::Orthographic { height: 1.0 }, 1.0);
+        let image = render(&file, &isometric, &get_flat_options());
+        assert_eq!(image.get_pixel(20, 20).eq(&Color::new(255, 0, 0, 255)), true);
+        assert_eq!(image.get_pixel(0, 0).eq(&Color::new(255, 255, 255, 255)), true);
+        assert_eq!(image.get_pixel(39, 39).eq(&Color::new(255, 255, 255, 255)), true);
+    }
+
+    #[test]
+    fn test_render_camera_inside_model() {
+        let file = get_file(vec![get_cube_element(Vector::new(0.0, 0.0, 0.0), Color::new(255, 0, 0, 255))]);
+        let camera = Camera::new(Vector::new(5.0, 5.0, 5.0), Vector::new(5.0, 5.0, 0.0), Vector::new(0.0, 1.0, 0.0),
+                                 Projection::Perspective { vertical_fov: 60.0 });
+        let image = render(&file, &camera, &get_flat_options());
+        assert_eq!(image.get_pixel(20, 20).eq(&Color::new(255, 0, 0, 255)), true);
+        assert_eq!(image.get_pixel(0, 0).eq(&Color::new(255, 0, 0, 255)), true);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_write_png() {
+        let file = get_file(vec![get_cube_element(Vector::new(0.0, 0.0, 0.0), Color::new(255, 0, 0, 255))]);
+        let image = render(&file, &get_top_camera(), &get_flat_options());
+        let mut bytes: Vec<u8> = Vec::new();
+        assert_eq!(image.write_png(&mut bytes).is_ok(), true);
+        assert_eq!(bytes[0..8].to_vec(), vec![137, 80, 78, 71, 13, 10, 26, 10]);
+
+        let decoder = png::Decoder::new(std::io::Cursor::new(bytes));
+        let mut reader = decoder.read_info().unwrap();
+        let mut decoded = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut decoded).unwrap();
+        assert_eq!(info.width, 40);
+        assert_eq!(info.height, 40);
+        assert_eq!(decoded[..info.buffer_size()].to_vec(), image.pixels);
+    }
+}
@@ -0,0 +1,554 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::color::Color;
+use crate::element::Element;
+use crate::file::File;
+use crate::mesh::Mesh;
+use crate::vector::Vector;
+
+/// Returns the points left after merging points lying within the tolerance from each other
+/// into the first of them, and for every given point the index of the point it was merged into.
+pub(crate) fn get_welded_points(points: &[Vector], tolerance: f64) -> (Vec<Vector>, Vec<usize>) {
+    let tolerance = tolerance.max(0.0);
+    let cell_size = if tolerance > 0.0 { tolerance } else { 1.0 };
+    let get_cell = |point: &Vector| -> [i64; 3] {
+        [(point.x / cell_size).floor() as i64, (point.y / cell_size).floor() as i64, (point.z / cell_size).floor() as i64]
+    };
+
+    let mut cells: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+    let mut kept: Vec<Vector> = Vec::new();
+    let mut point_map = Vec::with_capacity(points.len());
+    for point in points {
+        let cell = get_cell(point);
+        let mut found: Option<usize> = None;
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let neighbour = [cell[0] + dx, cell[1] + dy, cell[2] + dz];
+                    for &candidate in cells.get(&neighbour).into_iter().flatten() {
+                        if kept[candidate].distance_to(point) <= tolerance && found.is_none_or(|found| candidate < found) {
+                            found = Some(candidate);
+                        }
+                    }
+                }
+            }
+        }
+        let target = match found {
+            Some(target) => target,
+            None => {
+                kept.push(*point);
+                cells.entry(cell).or_default().push(kept.len() - 1);
+                kept.len() - 1
+            }
+        };
+        point_map.push(target);
+    }
+    (kept, point_map)
+}
+
+/// Represents the repair steps applied together by `Mesh::repair`, in the order of the fields.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RepairOptions {
+    /// Merges vertices lying within the tolerance from each other, None keeps them.
+    pub weld_tolerance: Option<f64>,
+    /// Removes triangles with zero area or with invalid indices.
+    pub remove_degenerate_triangles: bool,
+    /// Removes triangles using the same vertices as one of the earlier triangles.
+    pub remove_duplicate_triangles: bool,
+    /// Flips triangles to the same winding, facing outwards for closed parts.
+    pub unify_winding: bool,
+    /// Closes holes bounded by at most the given number of edges, None keeps them open.
+    pub fill_holes_max_edges: Option<usize>,
+    /// Removes vertices not used by any triangle.
+    pub remove_unreferenced_vertices: bool,
+}
+
+impl Default for RepairOptions {
+    fn default() -> Self {
+        RepairOptions {
+            weld_tolerance: Some(0.0),
+            remove_degenerate_triangles: true,
+            remove_duplicate_triangles: true,
+            unify_winding: true,
+            fill_holes_max_edges: None,
+            remove_unreferenced_vertices: true,
+        }
+    }
+}
+
+/// Returns the triangle map of two repairs applied one after another.
+fn chain_triangle_maps(first: &[Option<usize>], second: &[Option<usize>]) -> Vec<Option<usize>> {
+    second.iter().map(|index| index.and_then(|index| first[index])).collect()
+}
+
+impl Mesh {
+    /// Keeps only the triangles for which the predicate returns true.
+    /// Returns for every remaining triangle the index it had before.
+    fn retain_triangles<F: FnMut(usize) -> bool>(&mut self, mut keep: F) -> Vec<Option<usize>> {
+        let mut indices = Vec::with_capacity(self.indices.len());
+        let mut triangle_map = Vec::with_capacity(self.triangles_count());
+        for triangle_index in 0..self.triangles_count() {
+            if keep(triangle_index) {
+                indices.extend_from_slice(&self.indices[triangle_index * 3..triangle_index * 3 + 3]);
+                triangle_map.push(Some(triangle_index));
+            }
+        }
+        self.indices = indices;
+        triangle_map
+    }
+
+    /// Merges vertices lying within the tolerance from each other into the first of them,
+    /// so triangles of separately exported faces become connected.
+    /// Triangles are neither removed nor reordered, but some of them can become degenerate.
+    /// Returns the number of removed vertices.
+    pub fn weld_vertices(&mut self, tolerance: f64) -> usize {
+        let vertices: Vec<Vector> = (0..self.vertices_count()).map(|index| self.get_vertex(index)).collect();
+        let (kept, vertex_map) = get_welded_points(&vertices, tolerance);
+        for index in self.indices.iter_mut() {
+            if *index >= 0 && (*index as usize) < vertex_map.len() {
+                *index = vertex_map[*index as usize] as i32;
+            }
+        }
+        self.coordinates = kept.iter().flat_map(|vertex| [vertex.x, vertex.y, vertex.z]).collect();
+        vertices.len() - kept.len()
+    }
+
+    /// Removes triangles with zero area or with invalid indices.
+    /// Returns for every remaining triangle the index it had before.
+    pub fn remove_degenerate_triangles(&mut self) -> Vec<Option<usize>> {
+        let valid: Vec<bool> = (0..self.triangles_count())
+            .map(|index| match self.get_triangle(index) {
+                Some(triangle) => !self.is_degenerate_triangle(&triangle),
+                None => false,
+            })
+            .collect();
+        self.retain_triangles(|index| valid[index])
+    }
+
+    /// Removes triangles using the same vertices as one of the earlier triangles, in any order.
+    /// Returns for every remaining triangle the index it had before.
+    pub fn remove_duplicate_triangles(&mut self) -> Vec<Option<usize>> {
+        let mut seen: HashSet<[i32; 3]> = HashSet::new();
+        let indices = self.indices.clone();
+        self.retain_triangles(|index| {
+            let mut sorted = [indices[index * 3], indices[index * 3 + 1], indices[index * 3 + 2]];
+            sorted.sort();
+            seen.insert(sorted)
+        })
+    }
+
+    /// Removes vertices not used by any triangle. Invalid indices are replaced with -1.
+    /// Returns the number of removed vertices.
+    pub fn remove_unreferenced_vertices(&mut self) -> usize {
+        let vertices_count = self.vertices_count();
+        let mut vertex_map: Vec<Option<usize>> = vec![None; vertices_count];
+        for &index in &self.indices {
+            if index >= 0 && (index as usize) < vertices_count {
+                vertex_map[index as usize] = Some(0);
+            }
+        }
+        let mut coordinates = Vec::with_capacity(self.coordinates.len());
+        for (index, target) in vertex_map.iter_mut().enumerate() {
+            if target.is_some() {
+                *target = Some(coordinates.len() / 3);
+                coordinates.extend_from_slice(&self.coordinates[index * 3..index * 3 + 3]);
+            }
+        }
+        for index in self.indices.iter_mut() {
+            *index = if *index >= 0 && (*index as usize) < vertices_count {
+                vertex_map[*index as usize].unwrap() as i32
+            } else {
+                -1
+            };
+        }
+        self.coordinates = coordinates;
+        vertices_count - self.vertices_count()
+    }
+
+    /// Flips triangles so neighbouring triangles have the same winding, starting from the first triangle
+    /// of every connected part. Closed parts are then turned to face outwards.
+    /// Only edges shared by exactly two triangles propagate the orientation.
+    /// Triangles are not reordered. Returns the number of flipped triangles.
+    pub fn unify_winding(&mut self) -> usize {
+        let triangles_count = self.triangles_count();
+        let triangles: Vec<Option<[usize; 3]>> = (0..triangles_count)
+            .map(|index| self.get_triangle(index).filter(|t| t[0] != t[1] && t[1] != t[2] && t[2] != t[0]))
+            .collect();
+        // For every edge the triangles using it, with the direction they walk it: true if from the smaller index.
+        let mut edges: HashMap<[usize; 2], Vec<(usize, bool)>> = HashMap::new();
+        for (triangle_index, triangle) in triangles.iter().enumerate() {
+            if let Some(triangle) = triangle {
+                for corner in 0..3 {
+                    let start = triangle[corner];
+                    let end = triangle[(corner + 1) % 3];
+                    let key = if start < end { [start, end] } else { [end, start] };
+                    edges.entry(key).or_default().push((triangle_index, start < end));
+                }
+            }
+        }
+        let get_edge_key = |triangle: &[usize; 3], corner: usize| -> [usize; 2] {
+            let start = triangle[corner];
+            let end = triangle[(corner + 1) % 3];
+            if start < end { [start, end] } else { [end, start] }
+        };
+
+        let mut flipped = vec![false; triangles_count];
+        let mut visited = vec![false; triangles_count];
+        for seed in 0..triangles_count {
+            if triangles[seed].is_none() || visited[seed] {
+                continue;
+            }
+            visited[seed] = true;
+            let mut component = vec![seed];
+            let mut is_closed = true;
+            let mut queue = VecDeque::from([seed]);
+            while let Some(current) = queue.pop_front() {
+                let triangle = triangles[current].unwrap();
+                for corner in 0..3 {
+                    let users = &edges[&get_edge_key(&triangle, corner)];
+                    if users.len() != 2 {
+                        is_closed = false;
+                        continue;
+                    }
+                    let current_forward = users.iter().find(|user| user.0 == current).unwrap().1 != flipped[current];
+                    for &(neighbour, neighbour_forward) in users {
+                        if neighbour == current || visited[neighbour] {
+                            continue;
+                        }
+                        // The neighbour has to walk the shared edge in the opposite direction.
+                        flipped[neighbour] = neighbour_forward == current_forward;
+                        visited[neighbour] = true;
+                        component.push(neighbour);
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+
+            if is_closed {
+                let mut volume = 0.0;
+                for &index in &component {
+                    let triangle = triangles[index].unwrap();
+                    let a = self.get_vertex(triangle[0]);
+                    let b = self.get_vertex(triangle[1]);
+                    let c = self.get_vertex(triangle[2]);
+                    let part = a.dot(&b.cross(&c)) / 6.0;
+                    volume += if flipped[index] { -part } else { part };
+                }
+                if volume < 0.0 {
+                    for &index in &component {
+                        flipped[index] = !flipped[index];
+                    }
+                }
+            }
+        }
+
+        let mut count = 0;
+        for (index, flip) in flipped.iter().enumerate() {
+            if *flip {
+                self.indices.swap(index * 3 + 1, index * 3 + 2);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Closes holes bounded by at most the given number of edges, by appending a fan of triangles
+    /// following the winding of the surrounding triangles. Suited to small, nearly planar and convex holes.
+    /// Returns for every triangle the index it had before, None for the appended ones.
+    pub fn fill_small_holes(&mut self, max_edges: usize) -> Vec<Option<usize>> {
+        let mut edges: HashMap<[usize; 2], Vec<(usize, usize)>> = HashMap::new();
+        for triangle_index in 0..self.triangles_count() {
+            if let Some(triangle) = self.get_triangle(triangle_index) {
+                for corner in 0..3 {
+                    let start = triangle[corner];
+                    let end = triangle[(corner + 1) % 3];
+                    if start == end {
+                        continue;
+                    }
+                    let key = if start < end { [start, end] } else { [end, start] };
+                    edges.entry(key).or_default().push((start, end));
+                }
+            }
+        }
+
+        // The hole walks each boundary edge opposite to the triangle using it.
+        let mut next: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut boundary: Vec<(usize, usize)> = edges.values().filter(|users| users.len() == 1).map(|users| (users[0].1, users[0].0)).collect();
+        boundary.sort();
+        for &(start, end) in &boundary {
+            next.entry(start).or_default().push(end);
+        }
+
+        let mut triangle_map: Vec<Option<usize>> = (0..self.triangles_count()).map(Some).collect();
+        let mut used: HashSet<usize> = HashSet::new();
+        for &(start, _) in &boundary {
+            if used.contains(&start) {
+                continue;
+            }
+            let mut hole = vec![start];
+            let mut is_simple = true;
+            loop {
+                let last = hole[hole.len() - 1];
+                let following = match next.get(&last) {
+                    Some(following) if following.len() == 1 => following[0],
+                    _ => {
+                        is_simple = false;
+                        break;
+                    }
+                };
+                if following == start {
+                    break;
+                }
+                if hole.contains(&following) || hole.len() > max_edges {
+                    is_simple = false;
+                    break;
+                }
+                hole.push(following);
+            }
+            used.extend(hole.iter().copied());
+            if !is_simple || hole.len() < 3 || hole.len() > max_edges {
+                continue;
+            }
+            for i in 1..hole.len() - 1 {
+                self.indices.extend_from_slice(&[hole[0] as i32, hole[i] as i32, hole[i + 1] as i32]);
+                triangle_map.push(None);
+            }
+        }
+        triangle_map
+    }
+
+    /// Applies the repair steps enabled in the options.
+    /// Returns for every triangle the index it had before, None for the appended ones.
+    pub fn repair(&mut self, options: &RepairOptions) -> Vec<Option<usize>> {
+        let mut triangle_map: Vec<Option<usize>> = (0..self.triangles_count()).map(Some).collect();
+        if let Some(tolerance) = options.weld_tolerance {
+            self.weld_vertices(tolerance);
+        }
+        if options.remove_degenerate_triangles {
+            triangle_map = chain_triangle_maps(&triangle_map, &self.remove_degenerate_triangles());
+        }
+        if options.remove_duplicate_triangles {
+            triangle_map = chain_triangle_maps(&triangle_map, &self.remove_duplicate_triangles());
+        }
+        if options.unify_winding {
+            self.unify_winding();
+        }
+        if let Some(max_edges) = options.fill_holes_max_edges {
+            triangle_map = chain_triangle_maps(&triangle_map, &self.fill_small_holes(max_edges));
+        }
+        if options.remove_unreferenced_vertices {
+            self.remove_unreferenced_vertices();
+        }
+        triangle_map
+    }
+}
+
+impl Element {
+    /// Rearranges face colors after the triangles of the mesh have changed, given for every new triangle
+    /// the index it had before. New triangles, and triangles without a face color, get the color of the element.
+    /// Nothing is done for elements without face colors.
+    pub fn remap_face_colors(&mut self, triangle_map: &[Option<usize>]) {
+        if self.face_colors.is_none() {
+            return;
+        }
+        let colors: Vec<Color> = triangle_map.iter().map(|old_index| old_index.and_then(|index| self.face_color(index)).unwrap_or(self.color)).collect();
+        self.set_face_colors(&colors);
+    }
+}
+
+impl File {
+    /// Applies the repair to the mesh with the given mesh_id and remaps face colors of all elements using it.
+    /// The repair returns for every triangle the index it had before, like the repair methods of Mesh do.
+    /// Returns false if there is no such mesh.
+    pub fn repair_mesh<F: FnOnce(&mut Mesh) -> Vec<Option<usize>>>(&mut self, mesh_id: i32, repair: F) -> bool {
+        let mesh = match self.meshes.iter_mut().find(|mesh| mesh.mesh_id == mesh_id) {
+            Some(mesh) => mesh,
+            None => return false,
+        };
+        let triangle_map = repair(mesh);
+        for element in self.elements.iter_mut().filter(|element| element.mesh_id == mesh_id) {
+            element.remap_face_colors(&triangle_map);
+        }
+        true
+    }
+
+    /// Applies the repair steps enabled in the options to the mesh with the given mesh_id
+    /// and remaps face colors of all elements using it. Returns false if there is no such mesh.
+    pub fn repair_mesh_with(&mut self, mesh_id: i32, options: &RepairOptions) -> bool {
+        self.repair_mesh(mesh_id, |mesh| mesh.repair(options))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use crate::rotation::Rotation;
+    use crate::test_utils::get_cube;
+    use super::*;
+
+    /// Returns the cube with every triangle having its own vertices, slightly moved.
+    fn get_triangle_soup_cube() -> Mesh {
+        let cube = get_cube(1.0);
+        let mut coordinates = Vec::new();
+        for (position, index) in cube.indices.iter().enumerate() {
+            let vertex = cube.get_vertex(*index as usize);
+            let noise = position as f64 * 1e-9;
+            coordinates.extend_from_slice(&[vertex.x + noise, vertex.y - noise, vertex.z]);
+        }
+        Mesh::new(0, coordinates, (0..36).collect())
+    }
+
+    fn get_element_with_face_colors(triangles_count: i32) -> Element {
+        let face_colors = (0..triangles_count).flat_map(|i| [i, i, i, 255]).collect();
+        Element::new(0, Vector::new(0.0, 0.0, 0.0), Rotation::new(0.0, 0.0, 0.0, 1.0), String::from("a"),
+                     String::from("Cube"), Color::new(255, 0, 0, 255), Some(face_colors), HashMap::new())
+    }
+
+    #[test]
+    fn test_weld_vertices() {
+        let mut mesh = get_triangle_soup_cube();
+        assert_eq!(mesh.analyze_topology().is_closed(), false);
+        assert_eq!(mesh.weld_vertices(1e-6), 28);
+        assert_eq!(mesh.vertices_count(), 8);
+        assert_eq!(mesh.triangles_count(), 12);
+        assert_eq!(mesh.analyze_topology().is_valid_solid(), true);
+    }
+
+    #[test]
+    fn test_weld_vertices_zero_tolerance() {
+        let mut mesh = get_triangle_soup_cube();
+        assert_eq!(mesh.weld_vertices(0.0), 0);
+        let mut cube = get_cube(1.0);
+        cube.coordinates.extend_from_slice(&[1.0, 1.0, 1.0]);
+        cube.indices[0] = 8;
+        assert_eq!(cube.weld_vertices(0.0), 1);
+        assert_eq!(cube.vertices_count(), 8);
+        assert_eq!(cube.indices[0], 6);
+    }
+
+    #[test]
+    fn test_remove_degenerate_triangles() {
+        let mut mesh = get_cube(1.0);
+        mesh.indices.extend_from_slice(&[0, 0, 1, 0, 1, 9, 0, 1, 1]);
+        mesh.indices.splice(3..3, [4, 5, 5]);
+        let triangle_map = mesh.remove_degenerate_triangles();
+        assert_eq!(mesh, get_cube(1.0));
+        assert_eq!(triangle_map[0], Some(0));
+        assert_eq!(triangle_map[1], Some(2));
+        assert_eq!(triangle_map.len(), 12);
+    }
+
+    #[test]
+    fn test_remove_duplicate_triangles() {
+        let mut mesh = get_cube(1.0);
+        mesh.indices.splice(3..3, [1, 0, 2]);
+        let triangle_map = mesh.remove_duplicate_triangles();
+        assert_eq!(mesh, get_cube(1.0));
+        assert_eq!(triangle_map[..3], [Some(0), Some(2), Some(3)]);
+    }
+
+    #[test]
+    fn test_remove_unreferenced_vertices() {
+        let mut mesh = get_cube(1.0);
+        mesh.coordinates.splice(0..0, [5.0, 5.0, 5.0]);
+        for index in mesh.indices.iter_mut() {
+            *index += 1;
+        }
+        mesh.indices.extend_from_slice(&[1, 2, 20]);
+        assert_eq!(mesh.remove_unreferenced_vertices(), 1);
+        assert_eq!(mesh.vertices_count(), 8);
+        assert_eq!(mesh.indices[..36], get_cube(1.0).indices[..]);
+        assert_eq!(mesh.indices[36..], [0, 1, -1]);
+    }
+
+    #[test]
+    fn test_unify_winding() {
+        let mut mesh = get_cube(1.0);
+        mesh.indices.swap(7, 8);
+        mesh.indices.swap(13, 14);
+        assert_eq!(mesh.unify_winding(), 2);
+        assert_eq!(mesh, get_cube(1.0));
+    }
+
+    #[test]
+    fn test_unify_winding_turns_outwards() {
+        let mut mesh = get_cube(1.0);
+        for triangle in mesh.indices.chunks_mut(3) {
+            triangle.swap(1, 2);
+        }
+        // Flip one triangle back, so the winding starts inconsistent
+        mesh.indices.swap(1, 2);
+        assert_eq!(mesh.unify_winding(), 11);
+        assert_eq!(mesh.analyze_topology().is_valid_solid(), true);
+    }
+
+    #[test]
+    fn test_fill_small_holes() {
+        let mut mesh = get_cube(1.0);
+        // Remove the top side
+        mesh.indices.drain(6..12);
+        let mut too_small = mesh.clone();
+        assert_eq!(too_small.fill_small_holes(3).len(), 10);
+        let triangle_map = mesh.fill_small_holes(4);
+        assert_eq!(triangle_map.len(), 12);
+        assert_eq!(triangle_map[9], Some(9));
+        assert_eq!(triangle_map[10..], [None, None]);
+        assert_eq!(mesh.analyze_topology().is_valid_solid(), true);
+    }
+
+    #[test]
+    fn test_remap_face_colors() {
+        let mut element = get_element_with_face_colors(3);
+        element.remap_face_colors(&[Some(2), None, Some(0), Some(7)]);
+        assert_eq!(element.face_colors, Some(vec![2, 2, 2, 255, 255, 0, 0, 255, 0, 0, 0, 255, 255, 0, 0, 255]));
+
+        let mut without = get_element_with_face_colors(3);
+        without.face_colors = None;
+        without.remap_face_colors(&[Some(0)]);
+        assert_eq!(without.face_colors, None);
+    }
+
+    #[test]
+    fn test_repair_mesh() {
+        let mut mesh = get_cube(1.0);
+        mesh.indices.splice(3..3, [0, 0, 1]);
+        let mut other = get_element_with_face_colors(13);
+        other.mesh_id = 1;
+        let mut file = File::new(String::from("1.0.0"), vec![mesh],
+                                 vec![get_element_with_face_colors(13), other], HashMap::new());
+        assert_eq!(file.repair_mesh(0, |mesh| mesh.remove_degenerate_triangles()), true);
+        assert_eq!(file.meshes[0], get_cube(1.0));
+        let face_colors = file.elements[0].face_colors.as_ref().unwrap();
+        assert_eq!(face_colors.len(), 48);
+        assert_eq!(face_colors[..8], [0, 0, 0, 255, 2, 2, 2, 255]);
+        assert_eq!(file.elements[1].face_colors.as_ref().unwrap().len(), 52);
+        assert_eq!(file.repair_mesh(5, |mesh| mesh.remove_degenerate_triangles()), false);
+    }
+
+    #[test]
+    fn test_repair() {
+        let mut mesh = get_triangle_soup_cube();
+        // Duplicate of the first triangle, then a degenerate one, then the top side removed
+        mesh.indices.splice(3..3, [0, 1, 2, 0, 0, 1]);
+        mesh.indices.drain(12..18);
+        let options = RepairOptions { weld_tolerance: Some(1e-6), fill_holes_max_edges: Some(4), ..Default::default() };
+        let triangle_map = mesh.repair(&options);
+        assert_eq!(triangle_map[..3], [Some(0), Some(3), Some(4)]);
+        assert_eq!(triangle_map[10..], [None, None]);
+        assert_eq!(mesh.vertices_count(), 8);
+        assert_eq!(mesh.analyze_topology().is_valid_solid(), true);
+    }
+
+    #[test]
+    fn test_repair_mesh_with() {
+        let mut mesh = get_cube(1.0);
+        mesh.indices.splice(3..3, [0, 0, 1]);
+        mesh.coordinates.extend_from_slice(&[5.0, 5.0, 5.0]);
+        let mut file = File::new(String::from("1.0.0"), vec![mesh], vec![get_element_with_face_colors(13)], HashMap::new());
+        assert_eq!(file.repair_mesh_with(0, &RepairOptions::default()), true);
+        assert_eq!(file.meshes[0], get_cube(1.0));
+        let face_colors = file.elements[0].face_colors.as_ref().unwrap();
+        assert_eq!(face_colors.len(), 48);
+        assert_eq!(face_colors[..8], [0, 0, 0, 255, 2, 2, 2, 255]);
+        assert_eq!(file.repair_mesh_with(5, &RepairOptions::default()), false);
+    }
+}
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
+use crate::vector::Vector;
 
 /// Represents a rotation in three-dimensional space using a quaternion.
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
 pub struct Rotation {
     /// The x-coordinate of the quaternion.
     pub qx: f64,
@@ -22,9 +23,26 @@ impl PartialEq for Rotation {
 impl Rotation {
     /// Returns a new Rotation
     pub fn new(qx: f64, qy: f64, qz: f64, qw: f64) -> Rotation { Rotation { qx, qy, qz, qw } }
+
+    /// Returns the given Vector rotated by this Rotation.
+    /// The quaternion is normalized first, a zero quaternion is treated as no rotation.
+    pub fn rotate_vector(&self, vector: &Vector) -> Vector {
+        let norm = (self.qx * self.qx + self.qy * self.qy + self.qz * self.qz + self.qw * self.qw).sqrt();
+        if norm == 0.0 {
+            return *vector;
+        }
+        let q = Vector::new(self.qx / norm, self.qy / norm, self.qz / norm);
+        let w = self.qw / norm;
+        let t = q.cross(vector) * 2.0;
+        *vector + t * w + q.cross(&t)
+    }
+
+    /// Returns the Rotation turning vectors back, the conjugate of the quaternion.
+    pub fn inverse(&self) -> Rotation { Rotation::new(-self.qx, -self.qy, -self.qz, self.qw) }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use serde_json::from_str;
     use serde_json::to_string;
@@ -39,6 +57,14 @@ mod tests {
         assert_eq!(result.qw, 5.5);
     }
 
+    #[test]
+    fn test_inverse() {
+        let rotation = Rotation::new(0.3, -0.2, 0.5, 0.8);
+        let vector = Vector::new(1.0, 2.0, 3.0);
+        let actual = rotation.inverse().rotate_vector(&rotation.rotate_vector(&vector));
+        assert_eq!(actual.distance_to(&vector) < 1e-12, true);
+    }
+
     #[test]
     fn test_partialeq_true() {
         let a = Rotation::new(1.5, -2.3, 3.9, 5.5);
@@ -87,6 +113,32 @@ mod tests {
         assert_eq!(b.eq(&a), false);
     }
 
+    #[test]
+    fn test_rotate_vector_identity() {
+        let rotation = Rotation::new(0.0, 0.0, 0.0, 1.0);
+        let actual = rotation.rotate_vector(&Vector::new(1.5, -2.3, 3.9));
+        assert_eq!(Vector::new(1.5, -2.3, 3.9).eq(&actual), true);
+    }
+
+    #[test]
+    fn test_rotate_vector_90_degrees_around_z() {
+        let half = std::f64::consts::FRAC_PI_4;
+        let rotation = Rotation::new(0.0, 0.0, half.sin(), half.cos());
+        let actual = rotation.rotate_vector(&Vector::new(1.0, 0.0, 0.0));
+        assert_eq!((actual.x - 0.0).abs() < 1e-12, true);
+        assert_eq!((actual.y - 1.0).abs() < 1e-12, true);
+        assert_eq!((actual.z - 0.0).abs() < 1e-12, true);
+    }
+
+    #[test]
+    fn test_rotate_vector_not_normalized() {
+        let rotation = Rotation::new(0.0, 0.0, 2.0, 0.0);
+        let actual = rotation.rotate_vector(&Vector::new(1.0, 2.0, 3.0));
+        assert_eq!((actual.x + 1.0).abs() < 1e-12, true);
+        assert_eq!((actual.y + 2.0).abs() < 1e-12, true);
+        assert_eq!((actual.z - 3.0).abs() < 1e-12, true);
+    }
+
     #[test]
     fn test_to_json() {
         let input = Rotation::new(1.5, -2.3, 3.9, 5.5);
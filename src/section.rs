@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+use crate::color::Color;
+use crate::file::File;
+use crate::spatial::get_world_triangles;
+use crate::vector::Vector;
+
+/// Represents a section plane with an orthonormal basis, used to express points lying in it in 2D.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SectionPlane {
+    /// A point of the plane, the origin of its 2D coordinates.
+    pub origin: Vector,
+    /// The unit normal of the plane.
+    pub normal: Vector,
+    /// The unit direction of the first 2D coordinate.
+    pub x_axis: Vector,
+    /// The unit direction of the second 2D coordinate, such that x_axis, y_axis and normal are right-handed.
+    pub y_axis: Vector,
+}
+
+impl SectionPlane {
+    /// Returns a new SectionPlane, or None for the zero normal.
+    /// The x axis is horizontal when possible, so for a vertical plane the y axis points up,
+    /// and for a horizontal plane facing up the axes match the world X and Y axes.
+    pub fn new(origin: Vector, normal: Vector) -> Option<SectionPlane> {
+        let normal = normal.normalize();
+        if normal.length() == 0.0 {
+            return None;
+        }
+        let up = if normal.z.abs() < 0.9 { Vector::new(0.0, 0.0, 1.0) } else { Vector::new(0.0, 1.0, 0.0) };
+        let x_axis = up.cross(&normal).normalize();
+        let y_axis = normal.cross(&x_axis);
+        Some(SectionPlane { origin, normal, x_axis, y_axis })
+    }
+
+    /// Returns the 2D coordinates of the point projected onto the plane.
+    pub fn to_plane(&self, point: &Vector) -> (f64, f64) {
+        let offset = *point - self.origin;
+        (offset.dot(&self.x_axis), offset.dot(&self.y_axis))
+    }
+
+    /// Returns the world-space point with the given 2D coordinates in the plane.
+    pub fn to_world(&self, point: (f64, f64)) -> Vector { self.origin + self.x_axis * point.0 + self.y_axis * point.1 }
+}
+
+/// Represents a polyline made of points in the world space.
+#[derive(Clone, Debug)]
+pub struct Polyline {
+    /// The points of the polyline. For closed polylines the first point is not repeated at the end.
+    pub points: Vec<Vector>,
+    /// True if the last point connects back to the first one.
+    pub closed: bool,
+}
+
+impl PartialEq for Polyline {
+    fn eq(&self, other: &Self) -> bool {
+        self.closed == other.closed && self.points == other.points
+    }
+}
+
+impl Polyline {
+    /// Returns a new Polyline
+    pub fn new(points: Vec<Vector>, closed: bool) -> Polyline { Polyline { points, closed } }
+
+    /// Returns the total length of the polyline, including the closing segment of closed polylines.
+    pub fn length(&self) -> f64 {
+        let mut length: f64 = self.points.windows(2).map(|pair| pair[0].distance_to(&pair[1])).sum();
+        if self.closed && self.points.len() > 1 {
+            length += self.points[self.points.len() - 1].distance_to(&self.points[0]);
+        }
+        length
+    }
+
+    /// Returns the points of the polyline as 2D coordinates in the plane.
+    pub fn to_plane(&self, plane: &SectionPlane) -> Vec<(f64, f64)> {
+        self.points.iter().map(|point| plane.to_plane(point)).collect()
+    }
+}
+
+/// Represents the result of cutting a single element with a plane.
+#[derive(Clone, Debug)]
+pub struct SectionCut {
+    /// The globally unique identifier of the cut element.
+    pub guid: String,
+    /// The color of the cut element.
+    pub color: Color,
+    /// The polylines where the plane crosses the element.
+    pub polylines: Vec<Polyline>,
+}
+
+impl PartialEq for SectionCut {
+    fn eq(&self, other: &Self) -> bool {
+        self.guid == other.guid && self.color == other.color && self.polylines == other.polylines
+    }
+}
+
+/// Returns the key identifying the point exactly, treating negative and positive zero as equal.
+pub(crate) fn get_point_key(point: &Vector) -> [u64; 3] {
+    [(point.x + 0.0).to_bits(), (point.y + 0.0).to_bits(), (point.z + 0.0).to_bits()]
+}
+
+/// Returns the point where the plane crosses the edge (a, b), given signed distances of its ends.
+/// The ends are ordered first, so the same edge shared by two triangles gives exactly the same point.
+fn get_crossing_point(a: &Vector, distance_a: f64, b: &Vector, distance_b: f64) -> Vector {
+    let (a, distance_a, b, distance_b) = if get_point_key(a) <= get_point_key(b) {
+        (a, distance_a, b, distance_b)
+    } else {
+        (b, distance_b, a, distance_a)
+    };
+    if distance_a == 0.0 {
+        return *a;
+    }
+    if distance_b == 0.0 {
+        return *b;
+    }
+    let t = distance_a / (distance_a - distance_b);
+    *a + (*b - *a) * t
+}
+
+/// Joins segments sharing end points into polylines.
+fn join_segments(segments: &[(Vector, Vector)]) -> Vec<Polyline> {
+    let mut segments_at_point: HashMap<[u64; 3], Vec<usize>> = HashMap::new();
+    for (index, (start, end)) in segments.iter().enumerate() {
+        segments_at_point.entry(get_point_key(start)).or_default().push(index);
+        segments_at_point.entry(get_point_key(end)).or_default().push(index);
+    }
+
+    let mut used = vec![false; segments.len()];
+    let mut polylines = Vec::new();
+    for first in 0..segments.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let mut points = vec![segments[first].0, segments[first].1];
+
+        // Walk forward from the end, then backward from the start of the first segment.
+        for direction in 0..2 {
+            loop {
+                let last = if direction == 0 { points[points.len() - 1] } else { points[0] };
+                let next = segments_at_point[&get_point_key(&last)]
+                    .iter()
+                    .copied()
+                    .find(|index| !used[*index]);
+                let next = match next {
+                    Some(next) => next,
+                    None => break,
+                };
+                used[next] = true;
+                let (start, end) = segments[next];
+                let other = if get_point_key(&start) == get_point_key(&last) { end } else { start };
+                if direction == 0 {
+                    points.push(other);
+                } else {
+                    points.insert(0, other);
+                }
+            }
+        }
+
+        let closed = points.len() > 2 && get_point_key(&points[0]) == get_point_key(&points[points.len() - 1]);
+        if closed {
+            points.pop();
+        }
+        polylines.push(Polyline::new(points, closed));
+    }
+    polylines
+}
+
+impl File {
+    /// Returns polylines where the plane crosses world-space triangles of elements, grouped by element.
+    ///
+    /// Points of the polylines are in the world space and all lie in the plane. Their 2D coordinates are
+    /// returned by Polyline::to_plane, given the SectionPlane made from the same origin and normal.
+    /// Only elements crossed by the plane are returned, in the order of the elements list.
+    /// Faces lying exactly in the plane don't produce any polylines. None are returned for the zero normal.
+    pub fn section(&self, plane_origin: Vector, plane_normal: Vector) -> Vec<SectionCut> {
+        let normal = plane_normal.normalize();
+        if normal.length() == 0.0 {
+            return Vec::new();
+        }
+
+        let mut segments_per_element: Vec<Vec<(Vector, Vector)>> = vec![Vec::new(); self.elements.len()];
+        for triangle in get_world_triangles(self) {
+            let distances = triangle.vertices.map(|vertex| (vertex - plane_origin).dot(&normal));
+            // Vertices lying on the plane count as being above it, so every crossing is found exactly once.
+            let above = distances.map(|distance| distance >= 0.0);
+            if above[0] == above[1] && above[1] == above[2] {
+                continue;
+            }
+            let mut crossing = Vec::with_capacity(2);
+            for i in 0..3 {
+                let j = (i + 1) % 3;
+                if above[i] != above[j] {
+                    crossing.push(get_crossing_point(&triangle.vertices[i], distances[i], &triangle.vertices[j], distances[j]));
+                }
+            }
+            if get_point_key(&crossing[0]) != get_point_key(&crossing[1]) {
+                segments_per_element[triangle.element_index].push((crossing[0], crossing[1]));
+            }
+        }
+
+        let mut result = Vec::new();
+        for (element, segments) in self.elements.iter().zip(segments_per_element) {
+            if segments.is_empty() {
+                continue;
+            }
+            result.push(SectionCut {
+                guid: element.guid.clone(),
+                color: element.color,
+                polylines: join_segments(&segments),
+            });
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use crate::element::Element;
+    use crate::mesh::Mesh;
+    use crate::rotation::Rotation;
+    use crate::test_utils::get_cube;
+    use super::*;
+
+    fn get_open_plate_mesh() -> Mesh {
+        Mesh::new(
+            1,
+            vec![
+                0.0, 0.0, 0.0,
+                10.0, 0.0, 0.0,
+                10.0, 0.0, 10.0,
+                0.0, 0.0, 10.0
+            ],
+            vec![
+                0, 1, 2,
+                0, 2, 3
+            ]
+        )
+    }
+
+    fn get_element(mesh_id: i32, position: Vector, guid: &str, color: Color) -> Element {
+        Element::new(
+            mesh_id,
+            position,
+            Rotation::new(0., 0., 0., 1.),
+            String::from(guid),
+            String::from("Wall"),
+            color,
+            None,
+            HashMap::new(),
+        )
+    }
+
+    fn get_file() -> File {
+        File::new(
+            String::from("1.0.0"),
+            vec![get_cube(10.0), get_open_plate_mesh()],
+            vec![
+                get_element(0, Vector::new(0.0, 0.0, 0.0), "cube", Color::new(255, 0, 0, 255)),
+                get_element(1, Vector::new(20.0, 0.0, 0.0), "plate", Color::new(0, 255, 0, 255)),
+                get_element(0, Vector::new(0.0, 0.0, 50.0), "high cube", Color::new(0, 0, 255, 255)),
+            ],
+            HashMap::new(),
+        )
+    }
+
+    #[test]
+    fn test_section_closed_and_open() {
+        let file = get_file();
+        let actual = file.section(Vector::new(0.0, 0.0, 4.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(actual.len(), 2);
+
+        assert_eq!(actual[0].guid, "cube");
+        assert_eq!(actual[0].color.eq(&Color::new(255, 0, 0, 255)), true);
+        assert_eq!(actual[0].polylines.len(), 1);
+        assert_eq!(actual[0].polylines[0].closed, true);
+        assert_eq!((actual[0].polylines[0].length() - 40.0).abs() < 1e-9, true);
+        for point in &actual[0].polylines[0].points {
+            assert_eq!((point.z - 4.0).abs() < 1e-12, true);
+        }
+
+        assert_eq!(actual[1].guid, "plate");
+        assert_eq!(actual[1].polylines.len(), 1);
+        assert_eq!(actual[1].polylines[0].closed, false);
+        assert_eq!((actual[1].polylines[0].length() - 10.0).abs() < 1e-9, true);
+    }
+
+    #[test]
+    fn test_section_through_vertices() {
+        let file = get_file();
+        // The plane goes exactly through the bottom vertices of the high cube
+        let actual = file.section(Vector::new(0.0, 0.0, 50.0), Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].guid, "high cube");
+        assert_eq!(actual[0].polylines.len(), 1);
+        assert_eq!(actual[0].polylines[0].closed, true);
+        assert_eq!((actual[0].polylines[0].length() - 40.0).abs() < 1e-9, true);
+    }
+
+    #[test]
+    fn test_section_diagonal_plane() {
+        let file = get_file();
+        let actual = file.section(Vector::new(5.0, 5.0, 5.0), Vector::new(1.0, 1.0, 1.0));
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].polylines.len(), 1);
+        assert_eq!(actual[0].polylines[0].closed, true);
+        // Regular hexagon with side of 5 * sqrt(2)
+        assert_eq!(actual[0].polylines[0].points.len() >= 6, true);
+        assert_eq!((actual[0].polylines[0].length() - 30.0 * 2.0_f64.sqrt()).abs() < 1e-9, true);
+    }
+
+    #[test]
+    fn test_section_misses_everything() {
+        let file = get_file();
+        assert_eq!(file.section(Vector::new(0.0, 0.0, 30.0), Vector::new(0.0, 0.0, 1.0)).len(), 0);
+        assert_eq!(file.section(Vector::new(0.0, 0.0, 4.0), Vector::new(0.0, 0.0, 0.0)).len(), 0);
+    }
+
+    #[test]
+    fn test_polyline_length() {
+        let open = Polyline::new(vec![Vector::new(0.0, 0.0, 0.0), Vector::new(3.0, 4.0, 0.0), Vector::new(3.0, 4.0, 1.0)], false);
+        assert_eq!(open.length(), 6.0);
+        let closed = Polyline::new(vec![Vector::new(0.0, 0.0, 0.0), Vector::new(3.0, 0.0, 0.0), Vector::new(3.0, 4.0, 0.0)], true);
+        assert_eq!(closed.length(), 12.0);
+    }
+
+    #[test]
+    fn test_section_plane() {
+        let horizontal = SectionPlane::new(Vector::new(1.0, 2.0, 3.0), Vector::new(0.0, 0.0, 2.0)).unwrap();
+        assert_eq!(horizontal.x_axis.eq(&Vector::new(1.0, 0.0, 0.0)), true);
+        assert_eq!(horizontal.y_axis.eq(&Vector::new(0.0, 1.0, 0.0)), true);
+        assert_eq!(horizontal.to_plane(&Vector::new(4.0, 7.0, 10.0)), (3.0, 5.0));
+
+        let front = SectionPlane::new(Vector::new(0.0, 0.0, 0.0), Vector::new(0.0, -1.0, 0.0)).unwrap();
+        assert_eq!(front.x_axis.eq(&Vector::new(1.0, 0.0, 0.0)), true);
+        assert_eq!(front.y_axis.eq(&Vector::new(0.0, 0.0, 1.0)), true);
+
+        let diagonal = SectionPlane::new(Vector::new(1.0, 1.0, 1.0), Vector::new(1.0, 2.0, 3.0)).unwrap();
+        assert_eq!(diagonal.x_axis.dot(&diagonal.normal).abs() < 1e-12, true);
+        assert_eq!(diagonal.y_axis.dot(&diagonal.x_axis).abs() < 1e-12, true);
+        assert_eq!((diagonal.x_axis.cross(&diagonal.y_axis) - diagonal.normal).length() < 1e-12, true);
+        let point = diagonal.to_world((2.5, -1.5));
+        let (x, y) = diagonal.to_plane(&point);
+        assert_eq!((x - 2.5).abs() < 1e-12 && (y + 1.5).abs() < 1e-12, true);
+
+        assert_eq!(SectionPlane::new(Vector::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 0.0)).is_none(), true);
+    }
+
+    #[test]
+    fn test_polyline_to_plane() {
+        let file = get_file();
+        let plane = SectionPlane::new(Vector::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0)).unwrap();
+        let cuts = file.section(plane.origin, plane.normal);
+        let points = cuts[0].polylines[0].to_plane(&plane);
+        assert_eq!(points.len(), cuts[0].polylines[0].points.len());
+        for (point, world) in points.iter().zip(&cuts[0].polylines[0].points) {
+            assert_eq!(*point, (world.x, world.y));
+        }
+    }
+}
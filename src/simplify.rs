@@ -0,0 +1,430 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use crate::file::File;
+use crate::mesh::Mesh;
+use crate::vector::Vector;
+
+/// Symmetric 4x4 matrix measuring the sum of squared distances of a point to a set of planes,
+/// stored as its upper triangle: aa, ab, ac, ad, bb, bc, bd, cc, cd, dd.
+#[derive(Clone, Copy, Debug, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// Returns the quadric of the plane with the given unit normal, going through the point.
+    fn from_plane(normal: &Vector, point: &Vector) -> Quadric {
+        let (a, b, c) = (normal.x, normal.y, normal.z);
+        let d = -normal.dot(point);
+        Quadric([a * a, a * b, a * c, a * d, b * b, b * c, b * d, c * c, c * d, d * d])
+    }
+
+    fn add(&self, other: &Quadric) -> Quadric {
+        let mut sum = self.0;
+        for (value, other_value) in sum.iter_mut().zip(other.0.iter()) {
+            *value += other_value;
+        }
+        Quadric(sum)
+    }
+
+    /// Returns the sum of squared distances of the point to the planes.
+    fn error(&self, point: &Vector) -> f64 {
+        let [aa, ab, ac, ad, bb, bc, bd, cc, cd, dd] = self.0;
+        let (x, y, z) = (point.x, point.y, point.z);
+        let error = aa * x * x + 2.0 * ab * x * y + 2.0 * ac * x * z + 2.0 * ad * x
+            + bb * y * y + 2.0 * bc * y * z + 2.0 * bd * y
+            + cc * z * z + 2.0 * cd * z + dd;
+        error.max(0.0)
+    }
+
+    /// Returns the point with the smallest error, if it is uniquely defined.
+    fn get_optimal_point(&self) -> Option<Vector> {
+        let [aa, ab, ac, ad, bb, bc, bd, cc, cd, _] = self.0;
+        let determinant = aa * (bb * cc - bc * bc) - ab * (ab * cc - bc * ac) + ac * (ab * bc - bb * ac);
+        if determinant.abs() < 1e-10 {
+            return None;
+        }
+        // Cramer's rule for the gradient being zero
+        let (rx, ry, rz) = (-ad, -bd, -cd);
+        let x = rx * (bb * cc - bc * bc) - ab * (ry * cc - bc * rz) + ac * (ry * bc - bb * rz);
+        let y = aa * (ry * cc - rz * bc) - rx * (ab * cc - bc * ac) + ac * (ab * rz - ry * ac);
+        let z = aa * (bb * rz - bc * ry) - ab * (ab * rz - ry * ac) + rx * (ab * bc - bb * ac);
+        Some(Vector::new(x / determinant, y / determinant, z / determinant))
+    }
+}
+
+/// Candidate edge collapse, ordered so the cheapest one is popped first.
+struct Collapse {
+    cost: f64,
+    keep: usize,
+    remove: usize,
+    position: Vector,
+    versions: (usize, usize),
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool { self.cmp(other) == Ordering::Equal }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+            .then(other.keep.cmp(&self.keep))
+            .then(other.remove.cmp(&self.remove))
+    }
+}
+
+/// State of the mesh while edges are being collapsed.
+struct Simplifier {
+    positions: Vec<Vector>,
+    quadrics: Vec<Quadric>,
+    locked: Vec<bool>,
+    removed: Vec<bool>,
+    versions: Vec<usize>,
+    triangles: Vec<[usize; 3]>,
+    alive: Vec<bool>,
+    vertex_triangles: Vec<Vec<usize>>,
+}
+
+impl Simplifier {
+    /// Returns the cheapest collapse of the edge, or None if both vertices are locked.
+    fn get_collapse(&self, a: usize, b: usize) -> Option<Collapse> {
+        if self.locked[a] && self.locked[b] {
+            return None;
+        }
+        let (keep, remove) = if self.locked[b] { (b, a) } else { (a, b) };
+        let quadric = self.quadrics[keep].add(&self.quadrics[remove]);
+        let mut candidates = vec![self.positions[keep]];
+        if !self.locked[keep] {
+            candidates.extend(quadric.get_optimal_point());
+            candidates.push(self.positions[remove]);
+            candidates.push((self.positions[keep] + self.positions[remove]) * 0.5);
+        }
+        let (position, cost) = candidates
+            .into_iter()
+            .map(|candidate| (candidate, quadric.error(&candidate)))
+            .min_by(|x, y| x.1.total_cmp(&y.1))
+            .unwrap();
+        Some(Collapse { cost, keep, remove, position, versions: (self.versions[keep], self.versions[remove]) })
+    }
+
+    /// Returns the alive triangles using the vertex.
+    fn get_triangles_around(&self, vertex: usize) -> Vec<usize> {
+        self.vertex_triangles[vertex].iter().copied().filter(|triangle| self.alive[*triangle]).collect()
+    }
+
+    /// Returns the vertices sharing an alive triangle with the vertex.
+    fn get_neighbours(&self, vertex: usize) -> HashSet<usize> {
+        self.get_triangles_around(vertex)
+            .iter()
+            .flat_map(|triangle| self.triangles[*triangle])
+            .filter(|other| *other != vertex)
+            .collect()
+    }
+
+    fn get_normal(&self, triangle: &[usize; 3], moved: &[usize], position: &Vector) -> Vector {
+        let corners = triangle.map(|vertex| if moved.contains(&vertex) { *position } else { self.positions[vertex] });
+        (corners[1] - corners[0]).cross(&(corners[2] - corners[0]))
+    }
+
+    /// Applies the collapse if it keeps the surface manifold and doesn't flip any triangle.
+    /// Returns the number of removed triangles.
+    fn try_collapse(&mut self, collapse: &Collapse) -> Option<usize> {
+        let (keep, remove) = (collapse.keep, collapse.remove);
+        let around_remove = self.get_triangles_around(remove);
+        let around_keep = self.get_triangles_around(keep);
+        let shared: Vec<usize> = around_remove.iter().copied().filter(|t| self.triangles[*t].contains(&keep)).collect();
+        if shared.is_empty() {
+            return None;
+        }
+        let common = self.get_neighbours(keep).intersection(&self.get_neighbours(remove)).count();
+        if common != shared.len() {
+            return None;
+        }
+        for triangle in around_remove.iter().chain(around_keep.iter()) {
+            if shared.contains(triangle) {
+                continue;
+            }
+            let corners = self.triangles[*triangle];
+            let before = self.get_normal(&corners, &[], &collapse.position);
+            let after = self.get_normal(&corners, &[keep, remove], &collapse.position);
+            if after.length() <= 1e-12 * before.length() || before.dot(&after) <= 0.0 {
+                return None;
+            }
+        }
+
+        for triangle in &shared {
+            self.alive[*triangle] = false;
+        }
+        for triangle in around_remove {
+            if !self.alive[triangle] {
+                continue;
+            }
+            for vertex in self.triangles[triangle].iter_mut() {
+                if *vertex == remove {
+                    *vertex = keep;
+                }
+            }
+            self.vertex_triangles[keep].push(triangle);
+        }
+        self.positions[keep] = collapse.position;
+        self.quadrics[keep] = self.quadrics[keep].add(&self.quadrics[remove]);
+        self.removed[remove] = true;
+        self.versions[keep] += 1;
+        Some(shared.len())
+    }
+}
+
+impl Mesh {
+    /// Reduces the number of triangles by collapsing edges in the order of the smallest quadric error,
+    /// until there are at most target_triangles, or the next collapse would move the surface further
+    /// than max_error (measured as the square root of the sum of squared distances to the original planes).
+    ///
+    /// Vertices on boundary and non-manifold edges never move, so open edges stay as they are.
+    /// Vertices should be welded first. Triangles with invalid indices are dropped.
+    /// Returns for every remaining triangle the index it had before.
+    pub fn simplify(&mut self, target_triangles: usize, max_error: f64) -> Vec<Option<usize>> {
+        let vertices_count = self.vertices_count();
+        let mut triangles = Vec::new();
+        let mut original_indices = Vec::new();
+        for triangle_index in 0..self.triangles_count() {
+            if let Some(triangle) = self.get_triangle(triangle_index) {
+                if triangle[0] != triangle[1] && triangle[1] != triangle[2] && triangle[2] != triangle[0] {
+                    triangles.push(triangle);
+                    original_indices.push(triangle_index);
+                }
+            }
+        }
+
+        let positions: Vec<Vector> = (0..vertices_count).map(|index| self.get_vertex(index)).collect();
+        let mut quadrics = vec![Quadric::default(); vertices_count];
+        let mut vertex_triangles = vec![Vec::new(); vertices_count];
+        let mut edges: HashMap<[usize; 2], usize> = HashMap::new();
+        for (triangle_index, triangle) in triangles.iter().enumerate() {
+            let [a, b, c] = triangle.map(|vertex| positions[vertex]);
+            let normal = (b - a).cross(&(c - a)).normalize();
+            let quadric = Quadric::from_plane(&normal, &a);
+            for corner in 0..3 {
+                let start = triangle[corner];
+                let end = triangle[(corner + 1) % 3];
+                quadrics[start] = quadrics[start].add(&quadric);
+                vertex_triangles[start].push(triangle_index);
+                *edges.entry([start.min(end), start.max(end)]).or_default() += 1;
+            }
+        }
+        let mut locked = vec![false; vertices_count];
+        for (edge, count) in &edges {
+            if *count != 2 {
+                locked[edge[0]] = true;
+                locked[edge[1]] = true;
+            }
+        }
+
+        let mut simplifier = Simplifier {
+            positions,
+            quadrics,
+            locked,
+            removed: vec![false; vertices_count],
+            versions: vec![0; vertices_count],
+            alive: vec![true; triangles.len()],
+            triangles,
+            vertex_triangles,
+        };
+        let mut sorted_edges: Vec<[usize; 2]> = edges.into_keys().collect();
+        sorted_edges.sort();
+        let mut queue: BinaryHeap<Collapse> = sorted_edges.iter().filter_map(|edge| simplifier.get_collapse(edge[0], edge[1])).collect();
+
+        let max_cost = max_error * max_error;
+        let mut alive_count = simplifier.triangles.len();
+        while alive_count > target_triangles {
+            let collapse = match queue.pop() {
+                Some(collapse) => collapse,
+                None => break,
+            };
+            if simplifier.removed[collapse.keep] || simplifier.removed[collapse.remove]
+                || collapse.versions != (simplifier.versions[collapse.keep], simplifier.versions[collapse.remove]) {
+                continue;
+            }
+            if collapse.cost > max_cost {
+                break;
+            }
+            if let Some(removed_triangles) = simplifier.try_collapse(&collapse) {
+                alive_count -= removed_triangles;
+                let mut neighbours: Vec<usize> = simplifier.get_neighbours(collapse.keep).into_iter().collect();
+                neighbours.sort();
+                for neighbour in neighbours {
+                    queue.extend(simplifier.get_collapse(collapse.keep, neighbour));
+                }
+            }
+        }
+
+        // Remaining vertices keep their order, so nothing changes if no edge was collapsed.
+        let mut vertex_map: Vec<Option<usize>> = vec![None; vertices_count];
+        for (triangle_index, triangle) in simplifier.triangles.iter().enumerate() {
+            if simplifier.alive[triangle_index] {
+                for vertex in triangle {
+                    vertex_map[*vertex] = Some(0);
+                }
+            }
+        }
+        let mut coordinates = Vec::new();
+        for (vertex, new_index) in vertex_map.iter_mut().enumerate() {
+            if new_index.is_some() {
+                *new_index = Some(coordinates.len() / 3);
+                let position = simplifier.positions[vertex];
+                coordinates.extend_from_slice(&[position.x, position.y, position.z]);
+            }
+        }
+        let mut indices = Vec::new();
+        let mut triangle_map = Vec::new();
+        for (triangle_index, triangle) in simplifier.triangles.iter().enumerate() {
+            if simplifier.alive[triangle_index] {
+                indices.extend(triangle.iter().map(|vertex| vertex_map[*vertex].unwrap() as i32));
+                triangle_map.push(Some(original_indices[triangle_index]));
+            }
+        }
+        self.coordinates = coordinates;
+        self.indices = indices;
+        triangle_map
+    }
+}
+
+impl File {
+    /// Returns copies of the file with meshes simplified to the given fractions of their triangles,
+    /// one copy for every level. Face colors of elements follow the remaining triangles.
+    pub fn generate_lods(&self, levels: &[f64]) -> Vec<File> {
+        let mesh_ids: Vec<i32> = self.meshes.iter().map(|mesh| mesh.mesh_id).collect();
+        levels
+            .iter()
+            .map(|level| {
+                let ratio = level.clamp(0.0, 1.0);
+                let mut lod = self.clone();
+                for mesh_id in &mesh_ids {
+                    lod.repair_mesh(*mesh_id, |mesh| {
+                        let target = (mesh.triangles_count() as f64 * ratio).ceil() as usize;
+                        mesh.simplify(target, f64::INFINITY)
+                    });
+                }
+                lod
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::color::Color;
+    use crate::element::Element;
+    use crate::rotation::Rotation;
+    use super::*;
+
+    /// Returns a flat square grid of the given number of quads per side, lying in the XY plane.
+    fn get_grid(quads: usize) -> Mesh {
+        let mut coordinates = Vec::new();
+        for j in 0..=quads {
+            for i in 0..=quads {
+                coordinates.extend_from_slice(&[i as f64, j as f64, 0.0]);
+            }
+        }
+        let mut indices = Vec::new();
+        for j in 0..quads {
+            for i in 0..quads {
+                let corner = (j * (quads + 1) + i) as i32;
+                let row = (quads + 1) as i32;
+                indices.extend_from_slice(&[corner, corner + 1, corner + row + 1, corner, corner + row + 1, corner + row]);
+            }
+        }
+        Mesh::new(0, coordinates, indices)
+    }
+
+    /// Returns a cube from -1 to 1 with every side split into the given number of quads per side.
+    fn get_subdivided_cube(quads: usize) -> Mesh {
+        let sides = [
+            (Vector::new(-1.0, -1.0, 1.0), Vector::new(2.0, 0.0, 0.0), Vector::new(0.0, 2.0, 0.0)),
+            (Vector::new(-1.0, -1.0, -1.0), Vector::new(0.0, 2.0, 0.0), Vector::new(2.0, 0.0, 0.0)),
+            (Vector::new(1.0, -1.0, -1.0), Vector::new(0.0, 2.0, 0.0), Vector::new(0.0, 0.0, 2.0)),
+            (Vector::new(-1.0, -1.0, -1.0), Vector::new(0.0, 0.0, 2.0), Vector::new(0.0, 2.0, 0.0)),
+            (Vector::new(-1.0, 1.0, -1.0), Vector::new(0.0, 0.0, 2.0), Vector::new(2.0, 0.0, 0.0)),
+            (Vector::new(-1.0, -1.0, -1.0), Vector::new(2.0, 0.0, 0.0), Vector::new(0.0, 0.0, 2.0)),
+        ];
+        let grid = get_grid(quads);
+        let mut coordinates = Vec::new();
+        let mut indices = Vec::new();
+        for (origin, u, v) in sides {
+            let offset = (coordinates.len() / 3) as i32;
+            for index in 0..grid.vertices_count() {
+                let point = grid.get_vertex(index);
+                let vertex = origin + u * (point.x / quads as f64) + v * (point.y / quads as f64);
+                coordinates.extend_from_slice(&[vertex.x, vertex.y, vertex.z]);
+            }
+            indices.extend(grid.indices.iter().map(|index| index + offset));
+        }
+        let mut cube = Mesh::new(0, coordinates, indices);
+        cube.weld_vertices(1e-9);
+        cube
+    }
+
+    #[test]
+    fn test_simplify_flat_grid_keeps_boundary() {
+        let mut mesh = get_grid(4);
+        let triangle_map = mesh.simplify(0, f64::INFINITY);
+        assert_eq!(triangle_map.len(), mesh.triangles_count());
+        assert_eq!(mesh.triangles_count() < 32, true);
+        let report = mesh.analyze_topology();
+        assert_eq!(report.boundary_edges.len(), 16);
+        assert_eq!(report.components.len(), 1);
+        let area: f64 = mesh.face_normals().iter().enumerate().map(|(index, _)| {
+            let [a, b, c] = mesh.get_triangle(index).unwrap().map(|vertex| mesh.get_vertex(vertex));
+            (b - a).cross(&(c - a)).z / 2.0
+        }).sum();
+        assert_eq!((area - 16.0).abs() < 1e-9, true);
+    }
+
+    #[test]
+    fn test_simplify_subdivided_cube() {
+        let mut mesh = get_subdivided_cube(2);
+        assert_eq!(mesh.triangles_count(), 48);
+        mesh.simplify(0, 1e-6);
+        assert_eq!(mesh.triangles_count(), 12);
+        let report = mesh.analyze_topology();
+        assert_eq!(report.is_valid_solid(), true);
+        assert_eq!((report.signed_volume - 8.0).abs() < 1e-9, true);
+    }
+
+    #[test]
+    fn test_simplify_respects_max_error() {
+        let mut mesh = get_subdivided_cube(1);
+        let expected = mesh.clone();
+        assert_eq!(mesh.simplify(0, 1e-6).len(), 12);
+        assert_eq!(mesh, expected);
+    }
+
+    #[test]
+    fn test_simplify_to_target() {
+        let mut mesh = get_subdivided_cube(4);
+        mesh.simplify(100, f64::INFINITY);
+        assert_eq!(mesh.triangles_count() <= 100, true);
+        assert_eq!(mesh.triangles_count() > 90, true);
+        assert_eq!(mesh.analyze_topology().is_closed(), true);
+    }
+
+    #[test]
+    fn test_generate_lods() {
+        let mesh = get_subdivided_cube(2);
+        let face_colors = (0..48).flat_map(|i| [i, 0, 0, 255]).collect();
+        let element = Element::new(0, Vector::new(0.0, 0.0, 0.0), Rotation::new(0.0, 0.0, 0.0, 1.0), String::from("a"),
+                                   String::from("Cube"), Color::new(255, 0, 0, 255), Some(face_colors), HashMap::new());
+        let file = File::new(String::from("1.0.0"), vec![mesh], vec![element], HashMap::new());
+        let lods = file.generate_lods(&[1.0, 0.5]);
+        assert_eq!(lods.len(), 2);
+        assert_eq!(lods[0], file);
+        assert_eq!(lods[1].meshes[0].triangles_count() <= 24, true);
+        assert_eq!(lods[1].elements[0].face_colors.as_ref().unwrap().len(), lods[1].meshes[0].triangles_count() * 4);
+    }
+}
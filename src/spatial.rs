@@ -0,0 +1,625 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+use crate::file::File;
+use crate::vector::Vector;
+
+/// Maximum number of triangles stored in a single leaf of the hierarchy.
+const MAX_LEAF_SIZE: usize = 4;
+
+/// Represents an axis-aligned bounding box in three-dimensional space.
+#[derive(Clone, Copy, Debug)]
+pub struct BoundingBox {
+    /// The corner of the box with the smallest coordinates.
+    pub min: Vector,
+    /// The corner of the box with the largest coordinates.
+    pub max: Vector,
+}
+
+impl PartialEq for BoundingBox {
+    fn eq(&self, other: &Self) -> bool {
+        self.min == other.min && self.max == other.max
+    }
+}
+
+impl BoundingBox {
+    /// Returns a new BoundingBox
+    pub fn new(min: Vector, max: Vector) -> BoundingBox { BoundingBox { min, max } }
+
+    /// Returns an empty BoundingBox, which contains no points and can be extended later.
+    pub fn empty() -> BoundingBox {
+        BoundingBox::new(Vector::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+                         Vector::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY))
+    }
+
+    /// Returns the smallest BoundingBox containing all given points.
+    pub fn from_points(points: &[Vector]) -> BoundingBox {
+        let mut result = BoundingBox::empty();
+        for point in points {
+            result.extend(point);
+        }
+        result
+    }
+
+    /// Returns true if the BoundingBox doesn't contain any point.
+    pub fn is_empty(&self) -> bool { self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z }
+
+    /// Extends the BoundingBox so it contains the given point.
+    pub fn extend(&mut self, point: &Vector) {
+        self.min = Vector::new(self.min.x.min(point.x), self.min.y.min(point.y), self.min.z.min(point.z));
+        self.max = Vector::new(self.max.x.max(point.x), self.max.y.max(point.y), self.max.z.max(point.z));
+    }
+
+    /// Returns the smallest BoundingBox containing both this and other BoundingBox.
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        let mut result = *self;
+        result.extend(&other.min);
+        result.extend(&other.max);
+        result
+    }
+
+    /// Returns the BoundingBox grown by the given distance in every direction.
+    pub fn inflate(&self, distance: f64) -> BoundingBox {
+        let offset = Vector::new(distance, distance, distance);
+        BoundingBox::new(self.min - offset, self.max + offset)
+    }
+
+    /// Returns the center of the BoundingBox.
+    pub fn center(&self) -> Vector { (self.min + self.max) * 0.5 }
+
+    /// Returns the size of the BoundingBox along every axis.
+    pub fn size(&self) -> Vector { self.max - self.min }
+
+    /// Returns true if the point lies inside or on the boundary of the BoundingBox.
+    pub fn contains(&self, point: &Vector) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x
+            && point.y >= self.min.y && point.y <= self.max.y
+            && point.z >= self.min.z && point.z <= self.max.z
+    }
+
+    /// Returns true if this and other BoundingBox overlap or touch.
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min.x <= other.max.x && self.max.x >= other.min.x
+            && self.min.y <= other.max.y && self.max.y >= other.min.y
+            && self.min.z <= other.max.z && self.max.z >= other.min.z
+    }
+
+    /// Returns the distance from the point to the closest point of the BoundingBox, 0 if it is inside.
+    pub fn distance_to_point(&self, point: &Vector) -> f64 {
+        let dx = (self.min.x - point.x).max(0.0).max(point.x - self.max.x);
+        let dy = (self.min.y - point.y).max(0.0).max(point.y - self.max.y);
+        let dz = (self.min.z - point.z).max(0.0).max(point.z - self.max.z);
+        (dx * dx + dy * dy + dz * dz).sqrt()
+    }
+}
+
+/// Triangle of an element transformed to the world space.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Triangle {
+    /// Position of the element in the elements list of the file.
+    pub(crate) element_index: usize,
+    /// Index of the triangle in the mesh of the element.
+    pub(crate) triangle_index: usize,
+    /// World-space vertices of the triangle.
+    pub(crate) vertices: [Vector; 3],
+}
+
+impl Triangle {
+    pub(crate) fn bounding_box(&self) -> BoundingBox { BoundingBox::from_points(&self.vertices) }
+
+    pub(crate) fn centroid(&self) -> Vector { (self.vertices[0] + self.vertices[1] + self.vertices[2]) * (1.0 / 3.0) }
+}
+
+/// Returns world-space triangles of all elements in the file.
+/// Elements pointing to missing meshes and triangles with invalid indices are skipped.
+pub(crate) fn get_world_triangles(file: &File) -> Vec<Triangle> {
+    let mesh_positions = file.get_mesh_positions();
+    let mut triangles = Vec::new();
+    for (element_index, element) in file.elements.iter().enumerate() {
+        let mesh = match mesh_positions.get(&element.mesh_id) {
+            Some(position) => &file.meshes[*position],
+            None => continue,
+        };
+        let vertices = element.get_world_vertices(mesh);
+        for triangle_index in 0..mesh.triangles_count() {
+            if let Some([a, b, c]) = mesh.get_triangle(triangle_index) {
+                triangles.push(Triangle { element_index, triangle_index, vertices: [vertices[a], vertices[b], vertices[c]] });
+            }
+        }
+    }
+    triangles
+}
+
+/// Returns the point of the triangle (a, b, c) closest to the given point.
+pub(crate) fn closest_point_on_triangle(point: &Vector, a: &Vector, b: &Vector, c: &Vector) -> Vector {
+    let ab = *b - *a;
+    let ac = *c - *a;
+    let ap = *point - *a;
+    let d1 = ab.dot(&ap);
+    let d2 = ac.dot(&ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return *a;
+    }
+
+    let bp = *point - *b;
+    let d3 = ab.dot(&bp);
+    let d4 = ac.dot(&bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return *b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return *a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = *point - *c;
+    let d5 = ab.dot(&cp);
+    let d6 = ac.dot(&cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return *c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return *a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return *b + (*c - *b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denominator = va + vb + vc;
+    if denominator == 0.0 {
+        // Degenerate triangle, all regions above failed only because of rounding.
+        let candidates = [*a, *b, *c];
+        return *candidates.iter()
+            .min_by(|x, y| x.distance_to(point).total_cmp(&y.distance_to(point)))
+            .unwrap();
+    }
+    let v = vb / denominator;
+    let w = vc / denominator;
+    *a + ab * v + ac * w
+}
+
+/// Returns true if the triangle overlaps the BoundingBox, using the separating axis theorem.
+pub(crate) fn triangle_intersects_box(vertices: &[Vector; 3], bounding_box: &BoundingBox) -> bool {
+    if !BoundingBox::from_points(vertices).intersects(bounding_box) {
+        return false;
+    }
+
+    let center = bounding_box.center();
+    let half = bounding_box.size() * 0.5;
+    let v = [vertices[0] - center, vertices[1] - center, vertices[2] - center];
+    let edges = [v[1] - v[0], v[2] - v[1], v[0] - v[2]];
+    let box_axes = [Vector::new(1.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0), Vector::new(0.0, 0.0, 1.0)];
+
+    let mut axes = Vec::with_capacity(10);
+    axes.push(edges[0].cross(&edges[1]));
+    for box_axis in &box_axes {
+        for edge in &edges {
+            axes.push(box_axis.cross(edge));
+        }
+    }
+
+    for axis in &axes {
+        if axis.dot(axis) == 0.0 {
+            continue;
+        }
+        let p0 = v[0].dot(axis);
+        let p1 = v[1].dot(axis);
+        let p2 = v[2].dot(axis);
+        let radius = half.x * axis.x.abs() + half.y * axis.y.abs() + half.z * axis.z.abs();
+        if p0.min(p1).min(p2) > radius || p0.max(p1).max(p2) < -radius {
+            return false;
+        }
+    }
+    true
+}
+
+#[derive(Clone, Copy, Debug)]
+enum NodeContent {
+    /// Indices of the left and right child nodes.
+    Branch(usize, usize),
+    /// Range of the triangles stored in the leaf.
+    Leaf(usize, usize),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    bounds: BoundingBox,
+    content: NodeContent,
+}
+
+/// Entry of the best-first search queue, ordered so the closest entry is popped first.
+struct QueueEntry {
+    distance: f64,
+    node_index: Option<usize>,
+    triangle_index: usize,
+}
+
+impl PartialEq for QueueEntry {
+    fn eq(&self, other: &Self) -> bool { self.distance == other.distance }
+}
+
+impl Eq for QueueEntry {}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering { other.distance.total_cmp(&self.distance) }
+}
+
+/// Represents a bounding volume hierarchy built over world-space triangles of all elements of a file.
+///
+/// The hierarchy doesn't borrow the file, so query results refer to elements by their
+/// position in the elements list of the file it was built from.
+#[derive(Clone, Debug)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    triangles: Vec<Triangle>,
+    guids: Vec<String>,
+}
+
+impl Bvh {
+    /// Returns a new Bvh built over all elements of the given file
+    pub fn new(file: &File) -> Bvh {
+        let guids = file.elements.iter().map(|element| element.guid.clone()).collect();
+        Bvh::from_triangles(get_world_triangles(file), guids)
+    }
+
+    pub(crate) fn from_triangles(mut triangles: Vec<Triangle>, guids: Vec<String>) -> Bvh {
+        let mut nodes = Vec::new();
+        if !triangles.is_empty() {
+            let count = triangles.len();
+            build_node(&mut nodes, &mut triangles, 0, count);
+        }
+        Bvh { nodes, triangles, guids }
+    }
+
+    /// Returns the guid of the element at the given position of the elements list.
+    pub fn get_guid(&self, element_index: usize) -> &str { &self.guids[element_index] }
+
+    /// Returns the number of triangles stored in the hierarchy.
+    pub fn triangles_count(&self) -> usize { self.triangles.len() }
+
+    /// Returns the bounding box of all triangles, empty if there are none.
+    pub fn bounds(&self) -> BoundingBox {
+        match self.nodes.first() {
+            Some(root) => root.bounds,
+            None => BoundingBox::empty(),
+        }
+    }
+
+    /// Calls the visitor for every triangle whose bounding box overlaps the given one.
+    pub(crate) fn visit_overlapping<F: FnMut(&Triangle)>(&self, bounding_box: &BoundingBox, mut visitor: F) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if !node.bounds.intersects(bounding_box) {
+                continue;
+            }
+            match node.content {
+                NodeContent::Branch(left, right) => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+                NodeContent::Leaf(start, end) => {
+                    for triangle in &self.triangles[start..end] {
+                        if triangle.bounding_box().intersects(bounding_box) {
+                            visitor(triangle);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Calls the visitor for every node, skipping subtrees for which the filter returns false.
+    pub(crate) fn visit_filtered<P: FnMut(&BoundingBox) -> bool, F: FnMut(&Triangle)>(&self, mut filter: P, mut visitor: F) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if !filter(&node.bounds) {
+                continue;
+            }
+            match node.content {
+                NodeContent::Branch(left, right) => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+                NodeContent::Leaf(start, end) => {
+                    for triangle in &self.triangles[start..end] {
+                        visitor(triangle);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns sorted positions of elements that have at least one triangle intersecting the given box.
+    pub fn get_elements_intersecting(&self, bounding_box: &BoundingBox) -> Vec<usize> {
+        let mut found = HashSet::new();
+        self.visit_overlapping(bounding_box, |triangle| {
+            if !found.contains(&triangle.element_index) && triangle_intersects_box(&triangle.vertices, bounding_box) {
+                found.insert(triangle.element_index);
+            }
+        });
+        let mut result: Vec<usize> = found.into_iter().collect();
+        result.sort();
+        result
+    }
+
+    /// Returns up to k elements closest to the point, as pairs of element position and distance,
+    /// sorted from the closest one. The distance is measured to the closest triangle of an element.
+    pub fn get_nearest_elements(&self, point: &Vector, k: usize) -> Vec<(usize, f64)> {
+        let mut result = Vec::new();
+        if self.nodes.is_empty() || k == 0 {
+            return result;
+        }
+        let mut found = HashSet::new();
+        let mut queue = BinaryHeap::new();
+        queue.push(QueueEntry { distance: self.nodes[0].bounds.distance_to_point(point), node_index: Some(0), triangle_index: 0 });
+        while let Some(entry) = queue.pop() {
+            match entry.node_index {
+                None => {
+                    // Triangles come out of the queue ordered by their exact distance,
+                    // so the first triangle of each element determines its distance.
+                    let element_index = self.triangles[entry.triangle_index].element_index;
+                    if found.insert(element_index) {
+                        result.push((element_index, entry.distance));
+                        if result.len() == k {
+                            break;
+                        }
+                    }
+                }
+                Some(node_index) => match self.nodes[node_index].content {
+                    NodeContent::Branch(left, right) => {
+                        for child in [left, right] {
+                            queue.push(QueueEntry { distance: self.nodes[child].bounds.distance_to_point(point), node_index: Some(child), triangle_index: 0 });
+                        }
+                    }
+                    NodeContent::Leaf(start, end) => {
+                        for triangle_index in start..end {
+                            let triangle = &self.triangles[triangle_index];
+                            if found.contains(&triangle.element_index) {
+                                continue;
+                            }
+                            let [a, b, c] = triangle.vertices;
+                            let distance = closest_point_on_triangle(point, &a, &b, &c).distance_to(point);
+                            queue.push(QueueEntry { distance, node_index: None, triangle_index });
+                        }
+                    }
+                },
+            }
+        }
+        result
+    }
+
+    /// Returns all elements within the given distance from the point, as pairs of element position
+    /// and distance, sorted from the closest one.
+    pub fn get_elements_within_distance(&self, point: &Vector, distance: f64) -> Vec<(usize, f64)> {
+        let mut closest: Vec<(usize, f64)> = Vec::new();
+        let mut positions = std::collections::HashMap::new();
+        self.visit_filtered(|bounds| bounds.distance_to_point(point) <= distance, |triangle| {
+            let [a, b, c] = triangle.vertices;
+            let triangle_distance = closest_point_on_triangle(point, &a, &b, &c).distance_to(point);
+            if triangle_distance > distance {
+                return;
+            }
+            match positions.get(&triangle.element_index) {
+                Some(position) => {
+                    let entry: &mut (usize, f64) = &mut closest[*position];
+                    entry.1 = entry.1.min(triangle_distance);
+                }
+                None => {
+                    positions.insert(triangle.element_index, closest.len());
+                    closest.push((triangle.element_index, triangle_distance));
+                }
+            }
+        });
+        closest.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        closest
+    }
+}
+
+/// Builds the subtree for triangles in the given range and returns the index of its root node.
+fn build_node(nodes: &mut Vec<Node>, triangles: &mut [Triangle], start: usize, end: usize) -> usize {
+    let mut bounds = BoundingBox::empty();
+    let mut centroid_bounds = BoundingBox::empty();
+    for triangle in &triangles[start..end] {
+        bounds = bounds.union(&triangle.bounding_box());
+        centroid_bounds.extend(&triangle.centroid());
+    }
+
+    let node_index = nodes.len();
+    nodes.push(Node { bounds, content: NodeContent::Leaf(start, end) });
+    if end - start <= MAX_LEAF_SIZE {
+        return node_index;
+    }
+
+    let size = centroid_bounds.size();
+    let axis = if size.x >= size.y && size.x >= size.z { 0 } else if size.y >= size.z { 1 } else { 2 };
+    let middle = (start + end) / 2;
+    triangles[start..end].select_nth_unstable_by(middle - start, |a, b| {
+        a.centroid().get(axis).total_cmp(&b.centroid().get(axis))
+    });
+
+    let left = build_node(nodes, triangles, start, middle);
+    let right = build_node(nodes, triangles, middle, end);
+    nodes[node_index].content = NodeContent::Branch(left, right);
+    node_index
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::color::Color;
+    use crate::element::Element;
+    use crate::rotation::Rotation;
+    use crate::test_utils::get_cube;
+    use super::*;
+
+    fn get_cube_element(position: Vector, guid: &str) -> Element {
+        Element::new(
+            0,
+            position,
+            Rotation::new(0., 0., 0., 1.),
+            String::from(guid),
+            String::from("Brick"),
+            Color::new(255, 0, 0, 255),
+            None,
+            HashMap::new(),
+        )
+    }
+
+    /// Returns a row of 20 cubes, each cube placed 20 units further along x axis.
+    fn get_file_with_row_of_cubes() -> File {
+        let elements = (0..20)
+            .map(|i| get_cube_element(Vector::new(i as f64 * 20.0, 0.0, 0.0), &format!("cube-{}", i)))
+            .collect();
+        File::new(String::from("1.0.0"), vec![get_cube(10.0)], elements, HashMap::new())
+    }
+
+    #[test]
+    fn test_bounding_box_from_points() {
+        let actual = BoundingBox::from_points(&[Vector::new(1.0, -2.0, 3.0), Vector::new(-1.0, 5.0, 0.0)]);
+        assert_eq!(actual.eq(&BoundingBox::new(Vector::new(-1.0, -2.0, 0.0), Vector::new(1.0, 5.0, 3.0))), true);
+        assert_eq!(actual.is_empty(), false);
+        assert_eq!(BoundingBox::empty().is_empty(), true);
+    }
+
+    #[test]
+    fn test_bounding_box_intersects() {
+        let a = BoundingBox::new(Vector::new(0.0, 0.0, 0.0), Vector::new(10.0, 10.0, 10.0));
+        let b = BoundingBox::new(Vector::new(10.0, 5.0, 5.0), Vector::new(20.0, 20.0, 20.0));
+        let c = BoundingBox::new(Vector::new(10.1, 5.0, 5.0), Vector::new(20.0, 20.0, 20.0));
+        assert_eq!(a.intersects(&b), true);
+        assert_eq!(b.intersects(&a), true);
+        assert_eq!(a.intersects(&c), false);
+        assert_eq!(c.intersects(&a), false);
+    }
+
+    #[test]
+    fn test_bounding_box_distance_to_point() {
+        let a = BoundingBox::new(Vector::new(0.0, 0.0, 0.0), Vector::new(10.0, 10.0, 10.0));
+        assert_eq!(a.distance_to_point(&Vector::new(5.0, 5.0, 5.0)), 0.0);
+        assert_eq!(a.distance_to_point(&Vector::new(13.0, 14.0, 5.0)), 5.0);
+    }
+
+    #[test]
+    fn test_closest_point_on_triangle() {
+        let a = Vector::new(0.0, 0.0, 0.0);
+        let b = Vector::new(10.0, 0.0, 0.0);
+        let c = Vector::new(0.0, 10.0, 0.0);
+        assert_eq!(closest_point_on_triangle(&Vector::new(2.0, 2.0, 5.0), &a, &b, &c).eq(&Vector::new(2.0, 2.0, 0.0)), true);
+        assert_eq!(closest_point_on_triangle(&Vector::new(-5.0, -5.0, 0.0), &a, &b, &c).eq(&a), true);
+        assert_eq!(closest_point_on_triangle(&Vector::new(5.0, -5.0, 1.0), &a, &b, &c).eq(&Vector::new(5.0, 0.0, 0.0)), true);
+        assert_eq!(closest_point_on_triangle(&Vector::new(10.0, 10.0, 0.0), &a, &b, &c).eq(&Vector::new(5.0, 5.0, 0.0)), true);
+    }
+
+    #[test]
+    fn test_triangle_intersects_box() {
+        let triangle = [Vector::new(0.0, 0.0, 0.0), Vector::new(10.0, 0.0, 0.0), Vector::new(0.0, 10.0, 0.0)];
+        let inside = BoundingBox::new(Vector::new(1.0, 1.0, -1.0), Vector::new(2.0, 2.0, 1.0));
+        // Overlaps the bounding box of the triangle, but lies behind its hypotenuse
+        let outside = BoundingBox::new(Vector::new(8.0, 8.0, -1.0), Vector::new(9.0, 9.0, 1.0));
+        assert_eq!(triangle_intersects_box(&triangle, &inside), true);
+        assert_eq!(triangle_intersects_box(&triangle, &outside), false);
+    }
+
+    #[test]
+    fn test_bvh_new() {
+        let file = get_file_with_row_of_cubes();
+        let bvh = Bvh::new(&file);
+        assert_eq!(bvh.triangles_count(), 240);
+        assert_eq!(bvh.bounds().eq(&BoundingBox::new(Vector::new(0.0, 0.0, 0.0), Vector::new(390.0, 10.0, 10.0))), true);
+    }
+
+    #[test]
+    fn test_bvh_empty_file() {
+        let file = File::new(String::from("1.0.0"), vec![], vec![], HashMap::new());
+        let bvh = Bvh::new(&file);
+        assert_eq!(bvh.triangles_count(), 0);
+        assert_eq!(bvh.bounds().is_empty(), true);
+        assert_eq!(bvh.get_nearest_elements(&Vector::new(0.0, 0.0, 0.0), 3).len(), 0);
+        assert_eq!(bvh.get_elements_within_distance(&Vector::new(0.0, 0.0, 0.0), 3.0).len(), 0);
+    }
+
+    #[test]
+    fn test_get_elements_intersecting() {
+        let file = get_file_with_row_of_cubes();
+        let bvh = Bvh::new(&file);
+        let bounding_box = BoundingBox::new(Vector::new(25.0, 2.0, 2.0), Vector::new(65.0, 8.0, 8.0));
+        assert_eq!(bvh.get_elements_intersecting(&bounding_box), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_get_elements_intersecting_box_inside_cube() {
+        let file = get_file_with_row_of_cubes();
+        let bvh = Bvh::new(&file);
+        // The box lies inside the cube without touching any of its triangles
+        let bounding_box = BoundingBox::new(Vector::new(22.0, 2.0, 2.0), Vector::new(28.0, 8.0, 8.0));
+        assert_eq!(bvh.get_elements_intersecting(&bounding_box).len(), 0);
+    }
+
+    #[test]
+    fn test_get_nearest_elements() {
+        let file = get_file_with_row_of_cubes();
+        let bvh = Bvh::new(&file);
+        let actual = bvh.get_nearest_elements(&Vector::new(104.0, 5.0, 5.0), 3);
+        assert_eq!(actual.len(), 3);
+        assert_eq!(actual[0].0, 5);
+        assert_eq!(actual[0].1, 4.0);
+        assert_eq!(actual[1].0, 4);
+        assert_eq!(actual[1].1, 14.0);
+        assert_eq!(actual[2].0, 6);
+        assert_eq!(actual[2].1, 16.0);
+    }
+
+    #[test]
+    fn test_get_nearest_elements_more_than_available() {
+        let file = get_file_with_row_of_cubes();
+        let bvh = Bvh::new(&file);
+        let actual = bvh.get_nearest_elements(&Vector::new(0.0, 0.0, 0.0), 100);
+        assert_eq!(actual.len(), 20);
+        assert_eq!(actual[0].0, 0);
+        assert_eq!(actual[19].0, 19);
+    }
+
+    #[test]
+    fn test_get_elements_within_distance() {
+        let file = get_file_with_row_of_cubes();
+        let bvh = Bvh::new(&file);
+        let actual = bvh.get_elements_within_distance(&Vector::new(104.0, 5.0, 5.0), 15.0);
+        assert_eq!(actual, vec![(5, 4.0), (4, 14.0)]);
+    }
+
+    #[test]
+    fn test_bvh_matches_linear_scan() {
+        let file = get_file_with_row_of_cubes();
+        let bvh = Bvh::new(&file);
+        let triangles = get_world_triangles(&file);
+        let point = Vector::new(173.0, -12.0, 31.0);
+        let mut expected: Vec<(usize, f64)> = Vec::new();
+        for element_index in 0..file.elements.len() {
+            let distance = triangles.iter()
+                .filter(|triangle| triangle.element_index == element_index)
+                .map(|triangle| closest_point_on_triangle(&point, &triangle.vertices[0], &triangle.vertices[1], &triangle.vertices[2]).distance_to(&point))
+                .fold(f64::INFINITY, f64::min);
+            expected.push((element_index, distance));
+        }
+        expected.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let actual = bvh.get_nearest_elements(&point, 5);
+        assert_eq!(actual, expected[0..5].to_vec());
+    }
+}
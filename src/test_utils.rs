@@ -0,0 +1,21 @@
+use crate::mesh::Mesh;
+
+/// Returns a cube from the origin to (size, size, size) with 8 shared vertices and all triangles facing outwards.
+pub(crate) fn get_cube(size: f64) -> Mesh {
+    let coordinates = vec![0.0, 0.0, 0.0,
+                           1.0, 0.0, 0.0,
+                           1.0, 1.0, 0.0,
+                           0.0, 1.0, 0.0,
+                           0.0, 0.0, 1.0,
+                           1.0, 0.0, 1.0,
+                           1.0, 1.0, 1.0,
+                           0.0, 1.0, 1.0];
+    Mesh::new(0,
+              coordinates.into_iter().map(|value| value * size).collect(),
+              vec![0, 2, 1, 0, 3, 2, // Bottom
+                   4, 5, 6, 4, 6, 7, // Top
+                   0, 1, 5, 0, 5, 4, // Front
+                   1, 2, 6, 1, 6, 5, // Right
+                   2, 3, 7, 2, 7, 6, // Back
+                   3, 0, 4, 3, 4, 7]) // Left
+}
@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+use crate::color::Color;
+use crate::element::Element;
+use crate::file::File;
+use crate::palette::{categorical, Gradient};
+
+/// Represents the way values of elements are turned into colors.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColorScheme {
+    /// Every distinct value gets its own color of the categorical palette, in sorted order of the values.
+    Categorical,
+    /// Numeric values are placed on the gradient by the range, which defaults to the smallest and largest value.
+    Continuous { gradient: Gradient, range: Option<(f64, f64)> },
+    /// Numeric values are split into bins holding about the same number of elements, colored along the gradient.
+    /// Zero bins are treated as one.
+    Quantiles { gradient: Gradient, bins: usize },
+}
+
+/// Represents one color shown in a legend with the label describing it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LegendEntry {
+    /// The value, or the range of values, shown with the color.
+    pub label: String,
+    /// The color assigned.
+    pub color: Color,
+}
+
+/// Represents the legend of a thematic view.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Legend {
+    /// The entries of the legend. For continuous schemes they are evenly spaced samples of the gradient.
+    pub entries: Vec<LegendEntry>,
+    /// The number of elements that had no value, or no numeric value for numeric schemes, and were left unchanged.
+    pub unassigned: usize,
+}
+
+/// Returns the type of the element, to color elements by type.
+pub fn by_type(element: &Element) -> Option<String> { Some(element.element_type.clone()) }
+
+/// Returns a function returning the info value of elements under the given key.
+pub fn by_info(key: &str) -> impl Fn(&Element) -> Option<String> + '_ {
+    move |element| element.info.get(key).cloned()
+}
+
+/// Returns the value parsed as a finite number.
+fn parse_number(value: &str) -> Option<f64> { value.trim().parse::<f64>().ok().filter(|number| number.is_finite()) }
+
+/// Returns the number formatted for a label.
+fn format_number(value: f64) -> String { format!("{}", (value * 1e6).round() / 1e6) }
+
+/// Returns the thresholds splitting the sorted values into bins of about the same size, without duplicates.
+fn get_quantile_thresholds(sorted: &[f64], bins: usize) -> Vec<f64> {
+    let mut thresholds: Vec<f64> = (1..bins).map(|k| sorted[k * sorted.len() / bins]).collect();
+    thresholds.dedup();
+    thresholds.retain(|threshold| *threshold > sorted[0]);
+    thresholds
+}
+
+impl File {
+    /// Sets the color of every element from its value returned by the key function, following the scheme,
+    /// and returns the legend. Face colors of recolored elements are removed so the view shows the scheme.
+    /// Elements without a value are left unchanged.
+    pub fn colorize_by<F: Fn(&Element) -> Option<String>>(&mut self, key_fn: F, scheme: &ColorScheme) -> Legend {
+        let values: Vec<Option<String>> = self.elements.iter().map(key_fn).collect();
+        let (colors, entries) = match scheme {
+            ColorScheme::Categorical => {
+                let mut categories: BTreeMap<&String, Color> = values.iter().flatten().map(|value| (value, Color::TRANSPARENT)).collect();
+                for (color, category) in categorical(categories.len()).into_iter().zip(categories.values_mut()) {
+                    *category = color;
+                }
+                let colors: Vec<Option<Color>> = values.iter().map(|value| value.as_ref().map(|value| categories[value])).collect();
+                let entries = categories.into_iter().map(|(label, color)| LegendEntry { label: label.clone(), color }).collect();
+                (colors, entries)
+            }
+            ColorScheme::Continuous { gradient, range } => {
+                let numbers: Vec<Option<f64>> = values.iter().map(|value| value.as_deref().and_then(parse_number)).collect();
+                let (min, max) = range.unwrap_or_else(|| {
+                    numbers.iter().flatten().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), number| (min.min(*number), max.max(*number)))
+                });
+                let colors = numbers.iter().map(|number| number.map(|number| gradient.sample_range(number, min, max))).collect();
+                let entries = if min <= max {
+                    (0..5)
+                        .map(|i| {
+                            let value = min + (max - min) * i as f64 / 4.0;
+                            LegendEntry { label: format_number(value), color: gradient.sample_range(value, min, max) }
+                        })
+                        .collect()
+                } else {
+                    vec![]
+                };
+                (colors, entries)
+            }
+            ColorScheme::Quantiles { gradient, bins } => {
+                let numbers: Vec<Option<f64>> = values.iter().map(|value| value.as_deref().and_then(parse_number)).collect();
+                let mut sorted: Vec<f64> = numbers.iter().flatten().copied().collect();
+                sorted.sort_by(f64::total_cmp);
+                if sorted.is_empty() {
+                    (vec![None; numbers.len()], vec![])
+                } else {
+                    let thresholds = get_quantile_thresholds(&sorted, (*bins).max(1));
+                    let bin_colors = gradient.colors(thresholds.len() + 1);
+                    let colors = numbers
+                        .iter()
+                        .map(|number| number.map(|number| bin_colors[thresholds.iter().filter(|threshold| **threshold <= number).count()]))
+                        .collect();
+                    let mut bounds = vec![sorted[0]];
+                    bounds.extend_from_slice(&thresholds);
+                    bounds.push(sorted[sorted.len() - 1]);
+                    let entries = bin_colors
+                        .iter()
+                        .enumerate()
+                        .map(|(i, color)| LegendEntry { label: format!("{} - {}", format_number(bounds[i]), format_number(bounds[i + 1])), color: *color })
+                        .collect();
+                    (colors, entries)
+                }
+            }
+        };
+
+        let mut unassigned = 0;
+        for (element, color) in self.elements.iter_mut().zip(colors) {
+            match color {
+                Some(color) => {
+                    element.color = color;
+                    element.face_colors = None;
+                }
+                None => unassigned += 1,
+            }
+        }
+        Legend { entries, unassigned }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::palette::categorical_color;
+    use crate::primitives::cuboid;
+    use crate::rotation::Rotation;
+    use crate::vector::Vector;
+    use super::*;
+
+    fn get_file(values: &[(&str, Option<&str>)]) -> File {
+        let elements = values
+            .iter()
+            .enumerate()
+            .map(|(i, (element_type, cost))| {
+                let mut info = HashMap::new();
+                if let Some(cost) = cost {
+                    info.insert(String::from("Cost"), String::from(*cost));
+                }
+                Element::new(0, Vector::new(i as f64, 0.0, 0.0), Rotation::new(0.0, 0.0, 0.0, 1.0), format!("{}", i),
+                             String::from(*element_type), Color::new(1, 2, 3, 255), Some(vec![255, 0, 0, 255]), info)
+            })
+            .collect();
+        File::new(String::from("1.0.0"), vec![cuboid(0, 1.0, 1.0, 1.0)], elements, HashMap::new())
+    }
+
+    #[test]
+    fn test_colorize_by_type() {
+        let mut file = get_file(&[("Wall", None), ("Door", None), ("Wall", None)]);
+        let legend = file.colorize_by(by_type, &ColorScheme::Categorical);
+        assert_eq!(legend.unassigned, 0);
+        assert_eq!(legend.entries, vec![
+            LegendEntry { label: String::from("Door"), color: categorical_color(0) },
+            LegendEntry { label: String::from("Wall"), color: categorical_color(1) },
+        ]);
+        assert_eq!(file.elements[0].color, categorical_color(1));
+        assert_eq!(file.elements[1].color, categorical_color(0));
+        assert_eq!(file.elements[2].face_colors, None);
+    }
+
+    #[test]
+    fn test_colorize_by_info_categorical() {
+        let mut file = get_file(&[("Wall", Some("EI60")), ("Wall", None)]);
+        let legend = file.colorize_by(by_info("Cost"), &ColorScheme::Categorical);
+        assert_eq!(legend.entries.len(), 1);
+        assert_eq!(legend.unassigned, 1);
+        assert_eq!(file.elements[1].color, Color::new(1, 2, 3, 255));
+        assert_eq!(file.elements[1].face_colors.is_some(), true);
+    }
+
+    #[test]
+    fn test_colorize_by_continuous() {
+        let mut file = get_file(&[("Wall", Some("10")), ("Wall", Some("20")), ("Wall", Some("15")), ("Wall", Some("n/a"))]);
+        let gradient = Gradient::between(Color::BLACK, Color::WHITE);
+        let legend = file.colorize_by(by_info("Cost"), &ColorScheme::Continuous { gradient: gradient.clone(), range: None });
+        assert_eq!(legend.unassigned, 1);
+        assert_eq!(file.elements[0].color, Color::BLACK);
+        assert_eq!(file.elements[1].color, Color::WHITE);
+        assert_eq!(file.elements[2].color, Color::new(128, 128, 128, 255));
+        assert_eq!(legend.entries.len(), 5);
+        assert_eq!(legend.entries[1].label, "12.5");
+        assert_eq!(legend.entries[4].color, Color::WHITE);
+
+        let legend = file.colorize_by(by_info("Cost"), &ColorScheme::Continuous { gradient, range: Some((0.0, 40.0)) });
+        assert_eq!(file.elements[1].color, Color::new(128, 128, 128, 255));
+        assert_eq!(legend.entries[0].label, "0");
+    }
+
+    #[test]
+    fn test_colorize_by_quantiles() {
+        let costs = ["1", "2", "3", "4", "100", "200", "300", "400"];
+        let values: Vec<(&str, Option<&str>)> = costs.iter().map(|cost| ("Beam", Some(*cost))).collect();
+        let mut file = get_file(&values);
+        let gradient = Gradient::between(Color::BLACK, Color::WHITE);
+        let legend = file.colorize_by(by_info("Cost"), &ColorScheme::Quantiles { gradient, bins: 2 });
+        assert_eq!(legend.entries.len(), 2);
+        assert_eq!(legend.entries[0].label, "1 - 100");
+        assert_eq!(legend.entries[1].label, "100 - 400");
+        for (i, element) in file.elements.iter().enumerate() {
+            assert_eq!(element.color, if i < 4 { Color::BLACK } else { Color::WHITE });
+        }
+    }
+
+    #[test]
+    fn test_colorize_by_quantiles_with_ties_and_no_values() {
+        let mut file = get_file(&[("Beam", Some("5")), ("Beam", Some("5")), ("Beam", Some("5")), ("Beam", Some("9"))]);
+        let scheme = ColorScheme::Quantiles { gradient: Gradient::viridis(), bins: 4 };
+        let legend = file.colorize_by(by_info("Cost"), &scheme);
+        assert_eq!(legend.entries.len(), 2);
+        assert_eq!(file.elements[0].color == file.elements[3].color, false);
+
+        let mut single = get_file(&[("Beam", Some("5")), ("Beam", None)]);
+        let legend = single.colorize_by(by_info("Cost"), &ColorScheme::Quantiles { gradient: Gradient::viridis(), bins: 0 });
+        assert_eq!(legend.entries.len(), 1);
+        assert_eq!(legend.unassigned, 1);
+
+        let mut empty = get_file(&[("Beam", None)]);
+        let legend = empty.colorize_by(by_info("Cost"), &scheme);
+        assert_eq!(legend.entries.len(), 0);
+        assert_eq!(legend.unassigned, 1);
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::{HashMap, HashSet};
+use crate::mesh::Mesh;
+
+/// Relative tolerance below which the area of a triangle is treated as zero.
+const DEGENERATE_TOLERANCE: f64 = 1e-12;
+
+/// Represents the result of the topology analysis of a mesh.
+///
+/// Edges are identified by pairs of vertex indices, so vertices have to be shared between
+/// triangles for edges to be recognized as shared. Edges are stored with the smaller index first.
+#[derive(Clone, Debug, Default)]
+pub struct TopologyReport {
+    /// Edges used by only one triangle.
+    pub boundary_edges: Vec<[usize; 2]>,
+    /// Edges used by more than two triangles.
+    pub non_manifold_edges: Vec<[usize; 2]>,
+    /// Edges shared by two triangles that walk them in the same direction, so their winding disagrees.
+    pub inconsistent_edges: Vec<[usize; 2]>,
+    /// Triangles with zero area, including those using the same vertex more than once.
+    pub degenerate_triangles: Vec<usize>,
+    /// Triangles using the same vertices as one of the earlier triangles, in any order.
+    pub duplicate_triangles: Vec<usize>,
+    /// Triangles with negative indices or indices pointing outside the coordinates.
+    pub invalid_triangles: Vec<usize>,
+    /// Groups of triangles connected through shared edges, each sorted by triangle index.
+    pub components: Vec<Vec<usize>>,
+    /// The volume enclosed by the triangles, negative if they face inwards.
+    /// It is meaningful only if the mesh is closed and consistently oriented.
+    pub signed_volume: f64,
+}
+
+impl TopologyReport {
+    /// Returns true if every edge is shared by exactly two triangles.
+    pub fn is_closed(&self) -> bool {
+        self.boundary_edges.is_empty() && self.non_manifold_edges.is_empty() && !self.components.is_empty()
+    }
+
+    /// Returns true if no edge is shared by more than two triangles.
+    pub fn is_manifold(&self) -> bool { self.non_manifold_edges.is_empty() }
+
+    /// Returns true if all neighbouring triangles have the same winding.
+    pub fn is_consistently_oriented(&self) -> bool { self.inconsistent_edges.is_empty() }
+
+    /// Returns true if the mesh is a closed and consistently oriented shell facing outwards,
+    /// without degenerate, duplicate or invalid triangles, so volume-based quantities are meaningful.
+    pub fn is_valid_solid(&self) -> bool {
+        self.is_closed() && self.is_consistently_oriented() && self.degenerate_triangles.is_empty()
+            && self.duplicate_triangles.is_empty() && self.invalid_triangles.is_empty() && self.signed_volume > 0.0
+    }
+}
+
+/// Returns the root of the set containing the item, compressing the path on the way.
+pub(crate) fn find_root(parents: &mut [usize], item: usize) -> usize {
+    let mut root = item;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    let mut current = item;
+    while parents[current] != root {
+        let next = parents[current];
+        parents[current] = root;
+        current = next;
+    }
+    root
+}
+
+impl Mesh {
+    /// Returns true if the triangle with the given vertex indices has zero area.
+    pub(crate) fn is_degenerate_triangle(&self, triangle: &[usize; 3]) -> bool {
+        if triangle[0] == triangle[1] || triangle[1] == triangle[2] || triangle[2] == triangle[0] {
+            return true;
+        }
+        let a = self.get_vertex(triangle[0]);
+        let b = self.get_vertex(triangle[1]);
+        let c = self.get_vertex(triangle[2]);
+        let longest = (b - a).length().max((c - b).length()).max((a - c).length());
+        (b - a).cross(&(c - a)).length() <= DEGENERATE_TOLERANCE * longest * longest
+    }
+
+    /// Returns the analysis of edges, triangles and connectivity of the Mesh.
+    pub fn analyze_topology(&self) -> TopologyReport {
+        let mut report = TopologyReport::default();
+        // For every edge the triangles using it, with the direction they walk it: true if from the smaller index.
+        let mut edges: HashMap<[usize; 2], Vec<(usize, bool)>> = HashMap::new();
+        let mut seen_triangles: HashSet<[usize; 3]> = HashSet::new();
+        let mut valid_triangles = Vec::new();
+
+        for triangle_index in 0..self.triangles_count() {
+            let triangle = match self.get_triangle(triangle_index) {
+                Some(triangle) => triangle,
+                None => {
+                    report.invalid_triangles.push(triangle_index);
+                    continue;
+                }
+            };
+            valid_triangles.push(triangle_index);
+            let is_degenerate = self.is_degenerate_triangle(&triangle);
+            if is_degenerate {
+                report.degenerate_triangles.push(triangle_index);
+            }
+            let mut sorted = triangle;
+            sorted.sort();
+            if !seen_triangles.insert(sorted) {
+                report.duplicate_triangles.push(triangle_index);
+            }
+            if sorted[0] == sorted[1] || sorted[1] == sorted[2] {
+                // Triangles collapsed to an edge or a point don't connect anything.
+                continue;
+            }
+
+            let a = self.get_vertex(triangle[0]);
+            let b = self.get_vertex(triangle[1]);
+            let c = self.get_vertex(triangle[2]);
+            report.signed_volume += a.dot(&b.cross(&c)) / 6.0;
+
+            for corner in 0..3 {
+                let start = triangle[corner];
+                let end = triangle[(corner + 1) % 3];
+                let key = if start < end { [start, end] } else { [end, start] };
+                edges.entry(key).or_default().push((triangle_index, start < end));
+            }
+        }
+
+        let mut parents: Vec<usize> = (0..self.triangles_count()).collect();
+        let mut sorted_edges: Vec<&[usize; 2]> = edges.keys().collect();
+        sorted_edges.sort();
+        for edge in sorted_edges {
+            let users = &edges[edge];
+            match users.len() {
+                1 => report.boundary_edges.push(*edge),
+                2 => {
+                    if users[0].1 == users[1].1 {
+                        report.inconsistent_edges.push(*edge);
+                    }
+                }
+                _ => report.non_manifold_edges.push(*edge),
+            }
+            for pair in users.windows(2) {
+                let first = find_root(&mut parents, pair[0].0);
+                let second = find_root(&mut parents, pair[1].0);
+                parents[first.max(second)] = first.min(second);
+            }
+        }
+
+        let mut components: HashMap<usize, Vec<usize>> = HashMap::new();
+        for triangle_index in valid_triangles {
+            let root = find_root(&mut parents, triangle_index);
+            components.entry(root).or_default().push(triangle_index);
+        }
+        let mut components: Vec<Vec<usize>> = components.into_values().collect();
+        components.sort();
+        report.components = components;
+        report
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use crate::test_utils::get_cube;
+    use super::*;
+
+    #[test]
+    fn test_closed_cube() {
+        let report = get_cube(1.0).analyze_topology();
+        assert_eq!(report.is_closed(), true);
+        assert_eq!(report.is_manifold(), true);
+        assert_eq!(report.is_consistently_oriented(), true);
+        assert_eq!(report.is_valid_solid(), true);
+        assert_eq!(report.components.len(), 1);
+        assert_eq!(report.components[0].len(), 12);
+        assert_eq!((report.signed_volume - 1.0).abs() < 1e-12, true);
+    }
+
+    #[test]
+    fn test_open_cube() {
+        let mut mesh = get_cube(1.0);
+        // Remove the top side
+        mesh.indices.drain(6..12);
+        let report = mesh.analyze_topology();
+        assert_eq!(report.is_closed(), false);
+        assert_eq!(report.is_valid_solid(), false);
+        assert_eq!(report.boundary_edges, vec![[4, 5], [4, 7], [5, 6], [6, 7]]);
+    }
+
+    #[test]
+    fn test_flipped_cube() {
+        let mut mesh = get_cube(1.0);
+        for triangle in mesh.indices.chunks_mut(3) {
+            triangle.swap(1, 2);
+        }
+        let report = mesh.analyze_topology();
+        assert_eq!(report.is_closed(), true);
+        assert_eq!(report.is_consistently_oriented(), true);
+        assert_eq!((report.signed_volume + 1.0).abs() < 1e-12, true);
+        assert_eq!(report.is_valid_solid(), false);
+    }
+
+    #[test]
+    fn test_inconsistent_winding() {
+        let mut mesh = get_cube(1.0);
+        // Flip the first triangle of the top side
+        mesh.indices.swap(7, 8);
+        let report = mesh.analyze_topology();
+        assert_eq!(report.is_closed(), true);
+        assert_eq!(report.is_consistently_oriented(), false);
+        assert_eq!(report.inconsistent_edges, vec![[4, 5], [4, 6], [5, 6]]);
+    }
+
+    #[test]
+    fn test_non_manifold_and_components() {
+        // Three triangles sharing the edge (0, 1), and one separate triangle
+        let mesh = Mesh::new(0,
+                             vec![0.0, 0.0, 0.0,
+                                  1.0, 0.0, 0.0,
+                                  0.0, 1.0, 0.0,
+                                  0.0, -1.0, 0.0,
+                                  0.0, 0.0, 1.0,
+                                  5.0, 5.0, 5.0,
+                                  6.0, 5.0, 5.0,
+                                  5.0, 6.0, 5.0],
+                             vec![0, 1, 2, 1, 0, 3, 0, 1, 4, 5, 6, 7]);
+        let report = mesh.analyze_topology();
+        assert_eq!(report.non_manifold_edges, vec![[0, 1]]);
+        assert_eq!(report.is_manifold(), false);
+        assert_eq!(report.components, vec![vec![0, 1, 2], vec![3]]);
+    }
+
+    #[test]
+    fn test_degenerate_duplicate_and_invalid_triangles() {
+        let mesh = Mesh::new(0,
+                             vec![0.0, 0.0, 0.0,
+                                  1.0, 0.0, 0.0,
+                                  0.0, 1.0, 0.0,
+                                  2.0, 0.0, 0.0],
+                             vec![0, 1, 2, 2, 0, 1, 0, 1, 3, 0, 0, 2, 0, 1, 9, -1, 0, 1]);
+        let report = mesh.analyze_topology();
+        assert_eq!(report.duplicate_triangles, vec![1]);
+        assert_eq!(report.degenerate_triangles, vec![2, 3]);
+        assert_eq!(report.invalid_triangles, vec![4, 5]);
+        assert_eq!(report.is_valid_solid(), false);
+    }
+
+    #[test]
+    fn test_empty_mesh() {
+        let report = Mesh::new(0, vec![], vec![]).analyze_topology();
+        assert_eq!(report.is_closed(), false);
+        assert_eq!(report.components.len(), 0);
+    }
+}
@@ -0,0 +1,311 @@
+use crate::vector::Vector;
+
+/// Point of a planar polygon.
+type Point = (f64, f64);
+
+/// Returns twice the signed area of the triangle, positive if its corners are counterclockwise.
+fn get_cross(o: Point, a: Point, b: Point) -> f64 { (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0) }
+
+/// Returns true if the corner turns left, by more than rounding errors of nearly collinear points.
+fn is_convex(a: Point, b: Point, c: Point) -> bool {
+    let length_ab = ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt();
+    let length_bc = ((c.0 - b.0).powi(2) + (c.1 - b.1).powi(2)).sqrt();
+    get_cross(a, b, c) > 1e-12 * length_ab * length_bc
+}
+
+/// Returns twice the signed area of the polygon, positive if it is counterclockwise.
+fn get_signed_area(points: &[Point], polygon: &[usize]) -> f64 {
+    (0..polygon.len())
+        .map(|i| {
+            let a = points[polygon[i]];
+            let b = points[polygon[(i + 1) % polygon.len()]];
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum()
+}
+
+/// Returns true if the point lies inside or on the edges of the triangle, whatever its orientation.
+fn is_point_in_triangle(point: Point, a: Point, b: Point, c: Point) -> bool {
+    let ab = get_cross(a, b, point);
+    let bc = get_cross(b, c, point);
+    let ca = get_cross(c, a, point);
+    (ab >= 0.0 && bc >= 0.0 && ca >= 0.0) || (ab <= 0.0 && bc <= 0.0 && ca <= 0.0)
+}
+
+/// Returns true if the point lies inside the counterclockwise triangle, on its edges,
+/// or outside closer to an edge than rounding errors of nearly collinear points.
+fn is_point_near_triangle(point: Point, a: Point, b: Point, c: Point) -> bool {
+    [(a, b), (b, c), (c, a)].iter().all(|(p, q)| {
+        let length = ((q.0 - p.0).powi(2) + (q.1 - p.1).powi(2)).sqrt();
+        let distance = ((point.0 - p.0).powi(2) + (point.1 - p.1).powi(2)).sqrt();
+        get_cross(*p, *q, point) >= -1e-12 * length * length.max(distance)
+    })
+}
+
+/// Connects the hole to the counterclockwise polygon with a bridge going right from the rightmost
+/// point of the hole, so the polygon goes around the hole too. Returns false if no bridge was found.
+fn eliminate_hole(points: &[Point], polygon: &mut Vec<usize>, hole: &[usize]) -> bool {
+    let start = (0..hole.len())
+        .max_by(|a, b| points[hole[*a]].0.total_cmp(&points[hole[*b]].0).then(points[hole[*a]].1.total_cmp(&points[hole[*b]].1)))
+        .unwrap();
+    let m = points[hole[start]];
+
+    // The closest edge crossed by the ray going right from the hole.
+    let mut closest: Option<(f64, usize)> = None;
+    for k in 0..polygon.len() {
+        let next = (k + 1) % polygon.len();
+        let (a, b) = (points[polygon[k]], points[polygon[next]]);
+        if (a.1 > m.1) == (b.1 > m.1) {
+            continue;
+        }
+        let x = a.0 + (m.1 - a.1) * (b.0 - a.0) / (b.1 - a.1);
+        if x >= m.0 && closest.is_none_or(|(closest_x, _)| x < closest_x) {
+            closest = Some((x, if a.0 > b.0 { k } else { next }));
+        }
+    }
+    let (x, mut bridge) = match closest {
+        Some(closest) => closest,
+        None => return false,
+    };
+
+    // A reflex vertex inside the triangle between the ray and the chosen vertex would block the bridge,
+    // so the one closest in angle to the ray is used instead.
+    let intersection = (x, m.1);
+    let candidate = points[polygon[bridge]];
+    let mut best_tangent = f64::INFINITY;
+    for k in 0..polygon.len() {
+        let point = points[polygon[k]];
+        if point == candidate || point.0 <= m.0 {
+            continue;
+        }
+        let previous = points[polygon[(k + polygon.len() - 1) % polygon.len()]];
+        let next = points[polygon[(k + 1) % polygon.len()]];
+        if get_cross(previous, point, next) >= 0.0 || !is_point_in_triangle(point, m, intersection, candidate) {
+            continue;
+        }
+        let tangent = (point.1 - m.1).abs() / (point.0 - m.0);
+        if tangent < best_tangent {
+            best_tangent = tangent;
+            bridge = k;
+        }
+    }
+
+    let mut merged = Vec::with_capacity(polygon.len() + hole.len() + 2);
+    merged.extend_from_slice(&polygon[..=bridge]);
+    merged.extend((0..=hole.len()).map(|i| hole[(start + i) % hole.len()]));
+    merged.push(polygon[bridge]);
+    merged.extend_from_slice(&polygon[bridge + 1..]);
+    *polygon = merged;
+    true
+}
+
+/// Returns true if the triangle at the position of the polygon can be cut off.
+fn is_ear(points: &[Point], polygon: &[usize], position: usize) -> bool {
+    let count = polygon.len();
+    let a = points[polygon[(position + count - 1) % count]];
+    let b = points[polygon[position]];
+    let c = points[polygon[(position + 1) % count]];
+    if !is_convex(a, b, c) {
+        return false;
+    }
+    polygon.iter().all(|index| {
+        let point = points[*index];
+        point == a || point == b || point == c || !is_point_near_triangle(point, a, b, c)
+    })
+}
+
+/// Returns counterclockwise triangles covering the planar polygon with holes, found by ear clipping.
+/// Indices point into the outer loop followed by all holes, in the given order.
+/// Loops can have any orientation. Holes lying outside the outer loop are ignored.
+pub(crate) fn triangulate_polygon(outer: &[Point], holes: &[Vec<Point>]) -> Vec<[usize; 3]> {
+    let mut points: Vec<Point> = outer.to_vec();
+    let mut polygon: Vec<usize> = (0..outer.len()).collect();
+    if get_signed_area(&points, &polygon) < 0.0 {
+        polygon.reverse();
+    }
+    let mut hole_loops = Vec::new();
+    for hole in holes {
+        let mut hole_loop: Vec<usize> = (points.len()..points.len() + hole.len()).collect();
+        points.extend_from_slice(hole);
+        if hole_loop.len() < 3 {
+            continue;
+        }
+        if get_signed_area(&points, &hole_loop) > 0.0 {
+            hole_loop.reverse();
+        }
+        hole_loops.push(hole_loop);
+    }
+    if polygon.len() < 3 {
+        return Vec::new();
+    }
+
+    hole_loops.sort_by(|a, b| {
+        let get_max_x = |hole: &Vec<usize>| hole.iter().map(|index| points[*index].0).fold(f64::NEG_INFINITY, f64::max);
+        get_max_x(b).total_cmp(&get_max_x(a))
+    });
+    for hole in &hole_loops {
+        eliminate_hole(&points, &mut polygon, hole);
+    }
+
+    let mut triangles = Vec::with_capacity(polygon.len());
+    let mut position = 0;
+    let mut failures = 0;
+    while polygon.len() > 3 {
+        let count = polygon.len();
+        position %= count;
+        if failures < count && !is_ear(&points, &polygon, position) {
+            position += 1;
+            failures += 1;
+            continue;
+        }
+        // Without any ear left the polygon is degenerate, so the current vertex is cut off anyway.
+        let a = polygon[(position + count - 1) % count];
+        let c = polygon[(position + 1) % count];
+        if is_convex(points[a], points[polygon[position]], points[c]) {
+            triangles.push([a, polygon[position], c]);
+        }
+        polygon.remove(position);
+        position = (position + count - 2) % (count - 1);
+        failures = 0;
+    }
+    if is_convex(points[polygon[0]], points[polygon[1]], points[polygon[2]]) {
+        triangles.push([polygon[0], polygon[1], polygon[2]]);
+    }
+    triangles
+}
+
+/// Returns triangles covering the planar polygon with holes in 3D space, as indices for a Mesh.
+///
+/// Indices point into the outer loop followed by all holes, in the given order. Points are projected
+/// to the best-fit plane of the outer loop, so slightly non-planar polygons are supported as well.
+/// Triangles follow the winding of the outer loop, whatever the orientation of the holes.
+/// Nothing is returned for degenerate polygons.
+pub fn triangulate(outer: &[Vector], holes: &[Vec<Vector>]) -> Vec<i32> {
+    // Newell's method gives the normal of the best-fit plane, pointing along the winding of the loop.
+    let mut normal = Vector::new(0.0, 0.0, 0.0);
+    for i in 0..outer.len() {
+        let (a, b) = (outer[i], outer[(i + 1) % outer.len()]);
+        normal = normal + Vector::new((a.y - b.y) * (a.z + b.z), (a.z - b.z) * (a.x + b.x), (a.x - b.x) * (a.y + b.y));
+    }
+    let normal = normal.normalize();
+    if normal.length() == 0.0 {
+        return Vec::new();
+    }
+
+    let reference = if normal.x.abs() < 0.9 { Vector::new(1.0, 0.0, 0.0) } else { Vector::new(0.0, 1.0, 0.0) };
+    let u = reference.cross(&normal).normalize();
+    let v = normal.cross(&u);
+    let project = |points: &[Vector]| -> Vec<Point> { points.iter().map(|point| (point.dot(&u), point.dot(&v))).collect() };
+    let projected_holes: Vec<Vec<Point>> = holes.iter().map(|hole| project(hole)).collect();
+    triangulate_polygon(&project(outer), &projected_holes)
+        .iter()
+        .flatten()
+        .map(|index| *index as i32)
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use crate::rotation::Rotation;
+    use super::*;
+
+    fn get_area(points: &[Point], triangles: &[[usize; 3]]) -> f64 {
+        triangles.iter().map(|[a, b, c]| {
+            let area = get_cross(points[*a], points[*b], points[*c]) / 2.0;
+            assert_eq!(area > 0.0, true);
+            area
+        }).sum()
+    }
+
+    #[test]
+    fn test_triangulate_square() {
+        let square = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        let triangles = triangulate_polygon(&square, &[]);
+        assert_eq!(triangles.len(), 2);
+        assert_eq!(get_area(&square, &triangles), 1.0);
+    }
+
+    #[test]
+    fn test_triangulate_clockwise_concave() {
+        // L shape given clockwise
+        let shape = [(0.0, 0.0), (0.0, 3.0), (1.0, 3.0), (1.0, 1.0), (2.0, 1.0), (2.0, 0.0)];
+        let triangles = triangulate_polygon(&shape, &[]);
+        assert_eq!(triangles.len(), 4);
+        assert_eq!(get_area(&shape, &triangles), 4.0);
+    }
+
+    #[test]
+    fn test_triangulate_collinear_points() {
+        let shape = [(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (2.0, 1.0), (0.0, 1.0)];
+        let triangles = triangulate_polygon(&shape, &[]);
+        assert_eq!(triangles.len(), 3);
+        assert_eq!(get_area(&shape, &triangles), 2.0);
+    }
+
+    #[test]
+    fn test_triangulate_with_holes() {
+        let outer = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        let holes = vec![
+            vec![(2.0, 2.0), (4.0, 2.0), (4.0, 4.0), (2.0, 4.0)],
+            vec![(6.0, 6.0), (6.0, 8.0), (8.0, 8.0), (8.0, 6.0)],
+        ];
+        let triangles = triangulate_polygon(&outer, &holes);
+        let points: Vec<Point> = outer.iter().chain(holes.iter().flatten()).copied().collect();
+        assert_eq!(triangles.len(), 12 + 2 * 2 - 2);
+        assert_eq!((get_area(&points, &triangles) - 92.0).abs() < 1e-9, true);
+    }
+
+    #[test]
+    fn test_triangulate_degenerate() {
+        assert_eq!(triangulate_polygon(&[(0.0, 0.0), (1.0, 0.0)], &[]).len(), 0);
+        assert_eq!(triangulate_polygon(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0)], &[]).len(), 0);
+    }
+
+    /// Returns the sum of the vector areas of the triangles.
+    fn get_vector_area(points: &[Vector], indices: &[i32]) -> Vector {
+        indices.chunks(3).fold(Vector::new(0.0, 0.0, 0.0), |sum, triangle| {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| points[index as usize]);
+            sum + (b - a).cross(&(c - a)) * 0.5
+        })
+    }
+
+    #[test]
+    fn test_triangulate_vertical_concave_face() {
+        // L shape in the XZ plane, counterclockwise when seen from negative Y
+        let outer = [
+            Vector::new(0.0, 0.0, 0.0), Vector::new(2.0, 0.0, 0.0), Vector::new(2.0, 0.0, 1.0),
+            Vector::new(1.0, 0.0, 1.0), Vector::new(1.0, 0.0, 3.0), Vector::new(0.0, 0.0, 3.0),
+        ];
+        let indices = triangulate(&outer, &[]);
+        assert_eq!(indices.len(), 12);
+        let area = get_vector_area(&outer, &indices);
+        assert_eq!((area.y + 4.0).abs() < 1e-12, true);
+        assert_eq!(area.x.abs() < 1e-12 && area.z.abs() < 1e-12, true);
+    }
+
+    #[test]
+    fn test_triangulate_tilted_face_with_hole() {
+        let rotation = Rotation::new(0.3, 0.2, 0.1, 1.0);
+        let outer: Vec<Vector> = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]
+            .iter().map(|(x, y)| rotation.rotate_vector(&Vector::new(*x, *y, 0.0))).collect();
+        // The hole has the same orientation as the outer loop
+        let hole: Vec<Vector> = [(1.0, 1.0), (3.0, 1.0), (3.0, 3.0), (1.0, 3.0)]
+            .iter().map(|(x, y)| rotation.rotate_vector(&Vector::new(*x, *y, 0.0))).collect();
+        let holes = vec![hole];
+        let indices = triangulate(&outer, &holes);
+        assert_eq!(indices.len(), 8 * 3);
+        let points: Vec<Vector> = outer.iter().chain(holes[0].iter()).copied().collect();
+        let area = get_vector_area(&points, &indices);
+        let normal = rotation.rotate_vector(&Vector::new(0.0, 0.0, 1.0));
+        assert_eq!((area.dot(&normal) - 12.0).abs() < 1e-9, true);
+        assert_eq!((area.length() - 12.0).abs() < 1e-9, true);
+    }
+
+    #[test]
+    fn test_triangulate_degenerate_face() {
+        let line = [Vector::new(0.0, 0.0, 0.0), Vector::new(1.0, 1.0, 1.0), Vector::new(2.0, 2.0, 2.0)];
+        assert_eq!(triangulate(&line, &[]).len(), 0);
+        assert_eq!(triangulate(&[], &[]).len(), 0);
+    }
+}
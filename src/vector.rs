@@ -1,7 +1,8 @@
+use std::ops::{Add, Mul, Neg, Sub};
 use serde::{Deserialize, Serialize};
 
 /// Represents a three-dimensional vector with double-precision floating-point coordinates.
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
 pub struct Vector {
     /// The x-coordinate of the vector.
     pub x: f64,
@@ -17,12 +18,68 @@ impl PartialEq for Vector {
     }
 }
 
+impl Add for Vector {
+    type Output = Vector;
+    fn add(self, other: Vector) -> Vector { Vector::new(self.x + other.x, self.y + other.y, self.z + other.z) }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+    fn sub(self, other: Vector) -> Vector { Vector::new(self.x - other.x, self.y - other.y, self.z - other.z) }
+}
+
+impl Mul<f64> for Vector {
+    type Output = Vector;
+    fn mul(self, factor: f64) -> Vector { Vector::new(self.x * factor, self.y * factor, self.z * factor) }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+    fn neg(self) -> Vector { Vector::new(-self.x, -self.y, -self.z) }
+}
+
 impl Vector {
     /// Returns a new Vector
     pub fn new(x: f64, y: f64, z: f64) -> Vector { Vector { x, y, z } }
+
+    /// Returns the dot product of this and other Vector
+    pub fn dot(&self, other: &Vector) -> f64 { self.x * other.x + self.y * other.y + self.z * other.z }
+
+    /// Returns the cross product of this and other Vector
+    pub fn cross(&self, other: &Vector) -> Vector {
+        Vector::new(self.y * other.z - self.z * other.y,
+                    self.z * other.x - self.x * other.z,
+                    self.x * other.y - self.y * other.x)
+    }
+
+    /// Returns the length of the Vector
+    pub fn length(&self) -> f64 { self.dot(self).sqrt() }
+
+    /// Returns the distance between this and other point
+    pub fn distance_to(&self, other: &Vector) -> f64 { (*other - *self).length() }
+
+    /// Returns the Vector scaled to the length of 1.
+    /// Zero-length Vector is returned unchanged.
+    pub fn normalize(&self) -> Vector {
+        let length = self.length();
+        if length == 0.0 {
+            return *self;
+        }
+        Vector::new(self.x / length, self.y / length, self.z / length)
+    }
+
+    /// Returns the coordinate for the given axis: 0 for x, 1 for y, anything else for z.
+    pub fn get(&self, axis: usize) -> f64 {
+        match axis {
+            0 => self.x,
+            1 => self.y,
+            _ => self.z,
+        }
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use serde_json::from_str;
     use serde_json::to_string;
@@ -76,6 +133,35 @@ mod tests {
         assert_eq!(b.eq(&a), false);
     }
 
+    #[test]
+    fn test_add_sub() {
+        let a = Vector::new(1.5, -2.0, 3.0);
+        let b = Vector::new(0.5, 1.0, -1.0);
+        assert_eq!(Vector::new(2.0, -1.0, 2.0).eq(&(a + b)), true);
+        assert_eq!(Vector::new(1.0, -3.0, 4.0).eq(&(a - b)), true);
+        assert_eq!(Vector::new(-1.5, 2.0, -3.0).eq(&(-a)), true);
+        assert_eq!(Vector::new(3.0, -4.0, 6.0).eq(&(a * 2.0)), true);
+    }
+
+    #[test]
+    fn test_dot_cross() {
+        let x = Vector::new(1.0, 0.0, 0.0);
+        let y = Vector::new(0.0, 1.0, 0.0);
+        assert_eq!(x.dot(&y), 0.0);
+        assert_eq!(x.dot(&x), 1.0);
+        assert_eq!(Vector::new(0.0, 0.0, 1.0).eq(&x.cross(&y)), true);
+        assert_eq!(Vector::new(0.0, 0.0, -1.0).eq(&y.cross(&x)), true);
+    }
+
+    #[test]
+    fn test_length_and_normalize() {
+        let a = Vector::new(3.0, 0.0, 4.0);
+        assert_eq!(a.length(), 5.0);
+        assert_eq!(Vector::new(0.6, 0.0, 0.8).eq(&a.normalize()), true);
+        assert_eq!(Vector::new(0.0, 0.0, 0.0).eq(&Vector::new(0.0, 0.0, 0.0).normalize()), true);
+        assert_eq!(a.distance_to(&Vector::new(3.0, 0.0, 0.0)), 4.0);
+    }
+
     #[test]
     fn test_to_json() {
         let input = Vector::new(1.5, -2.3, 3.9);
@@ -0,0 +1,519 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+#[cfg(feature = "hash")]
+use sha2::{Digest, Sha256};
+use crate::color::Color;
+use crate::element::Element;
+use crate::file::File;
+use crate::mesh::Mesh;
+use crate::rotation::Rotation;
+use crate::vector::Vector;
+
+/// Represents options of writing files as JSON. With the default options the output is the same as the one of serde_json.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WriteOptions {
+    /// Writes keys of info maps in sorted order and negative zeros as zeros, so equal files are always written the same way.
+    pub canonical: bool,
+    /// The number of decimals mesh coordinates are rounded to.
+    pub precision: Option<u32>,
+    /// The spacing of the grid mesh coordinates are snapped to, applied before rounding to the precision.
+    pub grid: Option<f64>,
+    /// Writes whole numbers without the decimal part, e.g. 10 instead of 10.0.
+    pub strip_trailing_zeros: bool,
+    /// Writes the JSON indented with two spaces, one value per line, otherwise without any whitespace.
+    pub pretty: bool,
+}
+
+impl WriteOptions {
+    /// Returns the options of the canonical form.
+    pub fn canonical() -> WriteOptions { WriteOptions { canonical: true, ..Default::default() } }
+}
+
+/// Returns the value rounded to the given number of decimals. Values too large to have decimals are returned unchanged.
+fn round_to_decimals(value: f64, decimals: u32) -> f64 {
+    let factor = 10f64.powi(decimals.min(300) as i32);
+    let scaled = value * factor;
+    if !scaled.is_finite() || scaled.abs() >= 4503599627370496.0 {
+        return value;
+    }
+    scaled.round() / factor
+}
+
+/// Returns the number of decimals in the shortest representation of the value, if it is written without exponent.
+fn get_decimals(value: f64) -> Option<u32> {
+    let mut buffer = zmij::Buffer::new();
+    let formatted = buffer.format_finite(value);
+    if formatted.contains('e') {
+        return None;
+    }
+    formatted.split_once('.').map(|(_, decimals)| if decimals == "0" { 0 } else { decimals.len() as u32 })
+}
+
+/// Writes the parts of a file as JSON, with fields in the order they are declared in the structs.
+struct JsonWriter<W: Write> {
+    writer: W,
+    options: WriteOptions,
+    /// For every open object or list, whether anything was written into it yet.
+    open: Vec<bool>,
+}
+
+impl<W: Write> JsonWriter<W> {
+    fn write_raw(&mut self, value: &str) -> io::Result<()> { self.writer.write_all(value.as_bytes()) }
+
+    fn write_string(&mut self, value: &str) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, value).map_err(io::Error::from)
+    }
+
+    fn write_indent(&mut self) -> io::Result<()> {
+        self.write_raw("\n")?;
+        for _ in 0..self.open.len() {
+            self.write_raw("  ")?;
+        }
+        Ok(())
+    }
+
+    /// Opens an object or a list with the given bracket.
+    fn begin(&mut self, bracket: &str) -> io::Result<()> {
+        self.write_raw(bracket)?;
+        self.open.push(false);
+        Ok(())
+    }
+
+    /// Closes the last object or list with the given bracket.
+    fn end(&mut self, bracket: &str) -> io::Result<()> {
+        let has_items = self.open.pop().unwrap_or(false);
+        if self.options.pretty && has_items {
+            self.write_indent()?;
+        }
+        self.write_raw(bracket)
+    }
+
+    /// Writes what has to come before the next item of the open object or list.
+    fn begin_item(&mut self) -> io::Result<()> {
+        if let Some(has_items) = self.open.last_mut() {
+            let separate = *has_items;
+            *has_items = true;
+            if separate {
+                self.write_raw(",")?;
+            }
+        }
+        if self.options.pretty {
+            self.write_indent()?;
+        }
+        Ok(())
+    }
+
+    fn write_key(&mut self, key: &str) -> io::Result<()> {
+        self.begin_item()?;
+        self.write_string(key)?;
+        self.write_raw(if self.options.pretty { ": " } else { ":" })
+    }
+
+    fn write_i32(&mut self, value: i32) -> io::Result<()> { self.write_raw(&value.to_string()) }
+
+    fn write_f64(&mut self, value: f64) -> io::Result<()> {
+        if !value.is_finite() {
+            return self.write_raw("null");
+        }
+        let value = if self.options.canonical && value == 0.0 { 0.0 } else { value };
+        let mut buffer = zmij::Buffer::new();
+        let formatted = buffer.format_finite(value);
+        let formatted = match formatted.strip_suffix(".0") {
+            Some(whole) if self.options.strip_trailing_zeros => whole,
+            _ => formatted,
+        };
+        self.writer.write_all(formatted.as_bytes())
+    }
+
+    /// Writes the mesh coordinate, snapped to the grid and rounded to the precision of the options.
+    fn write_coordinate(&mut self, value: f64) -> io::Result<()> {
+        let mut value = value;
+        if let Some(grid) = self.options.grid.filter(|grid| grid.is_finite() && *grid > 0.0) {
+            value = (value / grid).round() * grid;
+            if let Some(decimals) = get_decimals(grid) {
+                value = round_to_decimals(value, decimals);
+            }
+        }
+        if let Some(precision) = self.options.precision {
+            value = round_to_decimals(value, precision);
+        }
+        if value == 0.0 && (self.options.grid.is_some() || self.options.precision.is_some()) {
+            value = 0.0;
+        }
+        self.write_f64(value)
+    }
+
+    fn write_list<T: Copy>(&mut self, values: &[T], write: fn(&mut Self, T) -> io::Result<()>) -> io::Result<()> {
+        self.begin("[")?;
+        for value in values {
+            self.begin_item()?;
+            write(self, *value)?;
+        }
+        self.end("]")
+    }
+
+    fn write_info(&mut self, info: &HashMap<String, String>) -> io::Result<()> {
+        let mut entries: Vec<(&String, &String)> = info.iter().collect();
+        if self.options.canonical {
+            entries.sort();
+        }
+        self.begin("{")?;
+        for (key, value) in entries {
+            self.write_key(key)?;
+            self.write_string(value)?;
+        }
+        self.end("}")
+    }
+
+    fn write_vector(&mut self, vector: &Vector) -> io::Result<()> {
+        self.begin("{")?;
+        for (key, value) in [("x", vector.x), ("y", vector.y), ("z", vector.z)] {
+            self.write_key(key)?;
+            self.write_f64(value)?;
+        }
+        self.end("}")
+    }
+
+    fn write_rotation(&mut self, rotation: &Rotation) -> io::Result<()> {
+        self.begin("{")?;
+        for (key, value) in [("qx", rotation.qx), ("qy", rotation.qy), ("qz", rotation.qz), ("qw", rotation.qw)] {
+            self.write_key(key)?;
+            self.write_f64(value)?;
+        }
+        self.end("}")
+    }
+
+    fn write_color(&mut self, color: &Color) -> io::Result<()> {
+        self.begin("{")?;
+        for (key, value) in [("r", color.r), ("g", color.g), ("b", color.b), ("a", color.a)] {
+            self.write_key(key)?;
+            self.write_i32(value)?;
+        }
+        self.end("}")
+    }
+
+    fn write_mesh(&mut self, mesh: &Mesh) -> io::Result<()> {
+        self.begin("{")?;
+        self.write_key("mesh_id")?;
+        self.write_i32(mesh.mesh_id)?;
+        self.write_key("coordinates")?;
+        self.write_list(&mesh.coordinates, Self::write_coordinate)?;
+        self.write_key("indices")?;
+        self.write_list(&mesh.indices, Self::write_i32)?;
+        self.end("}")
+    }
+
+    fn write_element(&mut self, element: &Element) -> io::Result<()> {
+        self.begin("{")?;
+        self.write_key("mesh_id")?;
+        self.write_i32(element.mesh_id)?;
+        self.write_key("vector")?;
+        self.write_vector(&element.vector)?;
+        self.write_key("rotation")?;
+        self.write_rotation(&element.rotation)?;
+        self.write_key("guid")?;
+        self.write_string(&element.guid)?;
+        self.write_key("type")?;
+        self.write_string(&element.element_type)?;
+        self.write_key("color")?;
+        self.write_color(&element.color)?;
+        if let Some(face_colors) = &element.face_colors {
+            self.write_key("face_colors")?;
+            self.write_list(face_colors, Self::write_i32)?;
+        }
+        self.write_key("info")?;
+        self.write_info(&element.info)?;
+        self.end("}")
+    }
+
+    fn write_file(&mut self, file: &File) -> io::Result<()> {
+        self.begin("{")?;
+        self.write_key("schema_version")?;
+        self.write_string(&file.schema_version)?;
+        self.write_key("meshes")?;
+        self.begin("[")?;
+        for mesh in &file.meshes {
+            self.begin_item()?;
+            self.write_mesh(mesh)?;
+        }
+        self.end("]")?;
+        self.write_key("elements")?;
+        self.begin("[")?;
+        for element in &file.elements {
+            self.begin_item()?;
+            self.write_element(element)?;
+        }
+        self.end("]")?;
+        self.write_key("info")?;
+        self.write_info(&file.info)?;
+        self.end("}")
+    }
+}
+
+/// Represents the list the stream writer is writing into.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Phase {
+    Meshes,
+    Elements,
+}
+
+/// Writes a file as JSON incrementally, one mesh or element at a time, so the whole file never has to be in memory.
+/// All meshes have to be written before the first element. The document is only valid after calling finish.
+/// Output is written in small pieces, so files should be wrapped in a BufWriter.
+pub struct StreamWriter<W: Write> {
+    json: JsonWriter<W>,
+    phase: Phase,
+}
+
+impl<W: Write> StreamWriter<W> {
+    /// Returns a new StreamWriter, after writing the beginning of the file with the given schema version.
+    pub fn new(writer: W, schema_version: &str, options: WriteOptions) -> io::Result<StreamWriter<W>> {
+        let mut json = JsonWriter { writer, options, open: Vec::new() };
+        json.begin("{")?;
+        json.write_key("schema_version")?;
+        json.write_string(schema_version)?;
+        json.write_key("meshes")?;
+        json.begin("[")?;
+        Ok(StreamWriter { json, phase: Phase::Meshes })
+    }
+
+    /// Writes the mesh. Fails with InvalidInput if an element was already written.
+    pub fn write_mesh(&mut self, mesh: &Mesh) -> io::Result<()> {
+        if self.phase != Phase::Meshes {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "meshes have to be written before elements"));
+        }
+        self.json.begin_item()?;
+        self.json.write_mesh(mesh)
+    }
+
+    /// Writes the element, closing the list of meshes when it is the first one.
+    pub fn write_element(&mut self, element: &Element) -> io::Result<()> {
+        self.begin_elements()?;
+        self.json.begin_item()?;
+        self.json.write_element(element)
+    }
+
+    /// Returns a mutable reference to the underlying writer, e.g. to drain a buffer between items.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.json.writer
+    }
+
+    fn begin_elements(&mut self) -> io::Result<()> {
+        if self.phase == Phase::Meshes {
+            self.json.end("]")?;
+            self.json.write_key("elements")?;
+            self.json.begin("[")?;
+            self.phase = Phase::Elements;
+        }
+        Ok(())
+    }
+
+    /// Writes the end of the file with the given info, flushes and returns the writer.
+    pub fn finish(mut self, info: &HashMap<String, String>) -> io::Result<W> {
+        self.begin_elements()?;
+        self.json.end("]")?;
+        self.json.write_key("info")?;
+        self.json.write_info(info)?;
+        self.json.end("}")?;
+        self.json.writer.flush()?;
+        Ok(self.json.writer)
+    }
+}
+
+/// Writes the file as JSON to the writer, following the options.
+pub fn write_file<W: Write>(file: &File, writer: W, options: &WriteOptions) -> io::Result<()> {
+    JsonWriter { writer, options: *options, open: Vec::new() }.write_file(file)
+}
+
+/// Returns the file written as JSON, following the options.
+pub fn to_string_with_options(file: &File, options: &WriteOptions) -> String {
+    let mut buffer = Vec::new();
+    write_file(file, &mut buffer, options).expect("Writing to memory cannot fail");
+    String::from_utf8(buffer).expect("Written JSON is valid UTF-8")
+}
+
+impl File {
+    /// Returns the file written in the canonical form, which is the same for equal files.
+    pub fn to_canonical_string(&self) -> String { to_string_with_options(self, &WriteOptions::canonical()) }
+
+    /// Returns the SHA-256 hash of the canonical form of the file, as a lowercase hex string.
+    #[cfg(feature = "hash")]
+    pub fn content_hash(&self) -> String {
+        Sha256::digest(self.to_canonical_string().as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use serde_json::{from_str, to_string};
+    use crate::primitives::cuboid;
+    use super::*;
+
+    fn get_file() -> File {
+        let mut info = HashMap::new();
+        for key in ["Name", "Level", "Fire rating", "Zone", "Cost", "\"Quoted\"\n"] {
+            info.insert(String::from(key), format!("Value of {}", key));
+        }
+        let mut mesh = cuboid(0, 1.5, 0.1, 1e-7);
+        mesh.coordinates[0] = -0.0;
+        mesh.coordinates[1] = 1e21;
+        mesh.coordinates[2] = 0.1 + 0.2;
+        let elements = vec![
+            Element::new(0, Vector::new(1.0, -2.5, 3.0), Rotation::new(0.0, 0.0, 0.0, 1.0), String::from("a"),
+                         String::from("Wall"), Color::new(255, 0, 0, 255), None, info.clone()),
+            Element::new(0, Vector::new(0.0, 0.0, 0.0), Rotation::new(0.1, 0.2, 0.3, 0.9), String::from("b"),
+                         String::from("Żółw ✓"), Color::new(1, 2, 3, 4), Some(vec![255, 0, 0, 255]), HashMap::new()),
+        ];
+        File::new(String::from("1.0.0"), vec![mesh], elements, info)
+    }
+
+    #[test]
+    fn test_default_matches_serde_json() {
+        let file = get_file();
+        assert_eq!(to_string_with_options(&file, &WriteOptions::default()), to_string(&file).unwrap());
+    }
+
+    #[test]
+    fn test_write_file() {
+        let file = get_file();
+        let mut buffer = Vec::new();
+        write_file(&file, &mut buffer, &WriteOptions::canonical()).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), file.to_canonical_string());
+    }
+
+    #[test]
+    fn test_canonical_string() {
+        let file = get_file();
+        let canonical = file.to_canonical_string();
+        let read: File = from_str(&canonical).unwrap();
+        assert_eq!(read.eq(&file), true);
+        assert_eq!(canonical.contains("\"coordinates\":[0.0,1e+21,0.30000000000000004,"), true);
+        let keys = ["\"\\\"Quoted\\\"\\n\"", "\"Cost\"", "\"Fire rating\"", "\"Level\"", "\"Name\"", "\"Zone\""];
+        let positions: Vec<usize> = keys.iter().map(|key| canonical.rfind(key).unwrap()).collect();
+        assert_eq!(positions.windows(2).all(|pair| pair[0] < pair[1]), true);
+    }
+
+    #[test]
+    fn test_canonical_string_independent_of_map_order() {
+        let file = get_file();
+        let mut rebuilt = file.clone();
+        let mut entries: Vec<(String, String)> = file.info.clone().into_iter().collect();
+        entries.reverse();
+        rebuilt.info = HashMap::new();
+        rebuilt.info.extend(entries);
+        assert_eq!(rebuilt.to_canonical_string(), file.to_canonical_string());
+    }
+
+    #[cfg(feature = "hash")]
+    #[test]
+    fn test_content_hash() {
+        let file = get_file();
+        let hash = file.content_hash();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase()), true);
+
+        let mut changed = file.clone();
+        changed.elements[0].color = Color::new(255, 0, 0, 254);
+        assert_eq!(changed.content_hash() == hash, false);
+
+        let empty = File::new(String::from("1.0.0"), vec![], vec![], HashMap::new());
+        let expected: String = Sha256::digest(b"{\"schema_version\":\"1.0.0\",\"meshes\":[],\"elements\":[],\"info\":{}}")
+            .iter().map(|byte| format!("{:02x}", byte)).collect();
+        assert_eq!(empty.content_hash(), expected);
+    }
+
+    #[test]
+    fn test_pretty_matches_serde_json() {
+        let file = get_file();
+        let options = WriteOptions { pretty: true, ..Default::default() };
+        assert_eq!(to_string_with_options(&file, &options), serde_json::to_string_pretty(&file).unwrap());
+        let empty = File::new(String::from("1.0.0"), vec![], vec![], HashMap::new());
+        assert_eq!(to_string_with_options(&empty, &options), serde_json::to_string_pretty(&empty).unwrap());
+    }
+
+    #[test]
+    fn test_strip_trailing_zeros() {
+        let mesh = Mesh::new(0, vec![10.0, -0.0, 2.5, 1e21, 1e-7, 100.0], vec![]);
+        let file = File::new(String::from("1.0.0"), vec![mesh], vec![], HashMap::new());
+        let options = WriteOptions { strip_trailing_zeros: true, ..Default::default() };
+        let written = to_string_with_options(&file, &options);
+        assert_eq!(written.contains("\"coordinates\":[10,-0,2.5,1e+21,1e-7,100]"), true);
+        let read: File = from_str(&written).unwrap();
+        assert_eq!(read.eq(&file), true);
+    }
+
+    #[test]
+    fn test_precision() {
+        let mesh = Mesh::new(0, vec![1234.56789, -0.0004, 0.1 + 0.2, 2.0 / 3.0, 1e300, 12.5], vec![]);
+        let file = File::new(String::from("1.0.0"), vec![mesh], vec![], HashMap::new());
+        let options = WriteOptions { precision: Some(3), strip_trailing_zeros: true, ..Default::default() };
+        let written = to_string_with_options(&file, &options);
+        assert_eq!(written.contains("\"coordinates\":[1234.568,0,0.3,0.667,1e+300,12.5]"), true);
+        let options = WriteOptions { precision: Some(0), ..Default::default() };
+        assert_eq!(to_string_with_options(&file, &options).contains("[1235.0,0.0,0.0,1.0,1e+300,13.0]"), true);
+    }
+
+    #[test]
+    fn test_grid() {
+        let mesh = Mesh::new(0, vec![0.26, 0.34, -0.07, 12.049, 7.0], vec![]);
+        let file = File::new(String::from("1.0.0"), vec![mesh], vec![], HashMap::new());
+        let options = WriteOptions { grid: Some(0.1), ..Default::default() };
+        assert_eq!(to_string_with_options(&file, &options).contains("[0.3,0.3,-0.1,12.0,7.0]"), true);
+        let options = WriteOptions { grid: Some(0.25), ..Default::default() };
+        assert_eq!(to_string_with_options(&file, &options).contains("[0.25,0.25,0.0,12.0,7.0]"), true);
+        let options = WriteOptions { grid: Some(5.0), precision: Some(2), strip_trailing_zeros: true, ..Default::default() };
+        assert_eq!(to_string_with_options(&file, &options).contains("[0,0,0,10,5]"), true);
+    }
+
+    #[test]
+    fn test_options_only_round_coordinates() {
+        let file = get_file();
+        let options = WriteOptions { precision: Some(0), ..Default::default() };
+        let written = to_string_with_options(&file, &options);
+        assert_eq!(written.contains("\"rotation\":{\"qx\":0.1,\"qy\":0.2,\"qz\":0.3,\"qw\":0.9}"), true);
+        assert_eq!(written.contains("\"vector\":{\"x\":1.0,\"y\":-2.5,\"z\":3.0}"), true);
+    }
+
+    fn write_with_stream_writer(file: &File, options: WriteOptions) -> String {
+        let mut stream = StreamWriter::new(Vec::new(), &file.schema_version, options).unwrap();
+        for mesh in &file.meshes {
+            stream.write_mesh(mesh).unwrap();
+        }
+        for element in &file.elements {
+            stream.write_element(element).unwrap();
+        }
+        String::from_utf8(stream.finish(&file.info).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_stream_writer() {
+        let file = get_file();
+        for options in [WriteOptions::default(), WriteOptions::canonical(), WriteOptions { pretty: true, precision: Some(2), ..Default::default() }] {
+            assert_eq!(write_with_stream_writer(&file, options), to_string_with_options(&file, &options));
+        }
+        let written = write_with_stream_writer(&file, WriteOptions::default());
+        let read: File = from_str(&written).unwrap();
+        assert_eq!(read.eq(&file), true);
+    }
+
+    #[test]
+    fn test_stream_writer_empty_and_without_elements() {
+        let mut file = File::new(String::from("1.0.0"), vec![], vec![], HashMap::new());
+        assert_eq!(write_with_stream_writer(&file, WriteOptions::default()), to_string(&file).unwrap());
+        file.meshes.push(cuboid(0, 1.0, 1.0, 1.0));
+        let options = WriteOptions { pretty: true, ..Default::default() };
+        assert_eq!(write_with_stream_writer(&file, options), serde_json::to_string_pretty(&file).unwrap());
+    }
+
+    #[test]
+    fn test_stream_writer_mesh_after_element() {
+        let file = get_file();
+        let mut stream = StreamWriter::new(Vec::new(), "1.0.0", WriteOptions::default()).unwrap();
+        stream.write_element(&file.elements[0]).unwrap();
+        let error = stream.write_mesh(&file.meshes[0]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        let written = stream.finish(&HashMap::new()).unwrap();
+        assert_eq!(from_str::<File>(std::str::from_utf8(&written).unwrap()).unwrap().elements.len(), 1);
+    }
+}